@@ -182,6 +182,14 @@ fn fragment(
         if (phase & 2) > 0 {
             ts.r = 1.;
         }
+    } else if (flags & (1<<1)) > 0 && (flags & 1) == 0 {
+        ts.r = 0.;
+        ts.g = temp * rc;
+    } else if (flags & (1<<2)) > 0 && (flags & 3) == 0 {
+        // charge is centered on 0.5, positive red and negative blue
+        let charge = temp * 2. - 1.;
+        ts.r = max(charge, 0.) * rc;
+        ts.b = max(-charge, 0.) * rc;
     } else if (flags & (1<<8)) > 0 {
         ts.r = f32(in.chunk_pos.x) * rc;
         ts.b = f32(in.chunk_pos.z) * rc;
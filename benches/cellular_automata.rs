@@ -56,9 +56,10 @@ fn criterion_benchmark(c: &mut Criterion) {
                     ChunkIter::new(&mut chunk),
                     ChunkGared::new(dummy, ChunkId::new(0, 0, 0)),
                     0,
+                    0,
                 );
                 #[cfg(not(debug_assertions))]
-                step(ChunkIter::new(&mut chunk), ChunkGared::new(dummy), 0);
+                step(ChunkIter::new(&mut chunk), ChunkGared::new(dummy), 0, 0);
             }
         })
     });
@@ -83,9 +84,10 @@ fn criterion_benchmark(c: &mut Criterion) {
                     ChunkIter::new(&mut chunk),
                     ChunkGared::new(dummy, ChunkId::new(0, 0, 0)),
                     0,
+                    0,
                 );
                 #[cfg(not(debug_assertions))]
-                step(ChunkIter::new(&mut chunk), ChunkGared::new(dummy), 0);
+                step(ChunkIter::new(&mut chunk), ChunkGared::new(dummy), 0, 0);
             }
         })
     });
@@ -1,5 +1,5 @@
-use crate::THERMAL_CONDUCTIVITY;
 use crate::{BlockProperties, FixedNum};
+use crate::{ELECTRICAL_CONDUCTIVITY, THERMAL_CONDUCTIVITY};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BlockMeta {
@@ -37,6 +37,18 @@ impl BlockMeta {
         }
         THERMAL_CONDUCTIVITY[index]
     }
+
+    /// Fraction of the charge difference that flows between two blocks each step
+    /// Void never conducts
+    pub const fn e_conductivity(&self, other: u8) -> FixedNum {
+        if other == 255 || self.id == 255 {
+            return FixedNum::ZERO;
+        }
+        let i = max(self.id, other) as usize;
+        let j = min(self.id, other) as usize;
+        let index = (i * (i + 1)) / 2 + j;
+        ELECTRICAL_CONDUCTIVITY[index]
+    }
 }
 
 const fn min(a: u8, b: u8) -> u8 {
@@ -23,10 +23,15 @@ pub fn make_block_meta_file<T: Iterator<Item: AsRef<str>>>(iter: T) {
     //     ron::to_string_prety(&RawBlockProperties::VOID, ron::ser::PrettyConfig::default()).unwrap()
     // );
 
-    for block in iter {
-        let path = format!("assets/blocks/{}.block", block.as_ref());
+    // decay products are named in the files, so every name is needed before any are read
+    let blocks = iter.map(|b| b.as_ref().to_string()).collect::<Vec<_>>();
+    for block in &blocks {
+        let path = format!("assets/blocks/{}.block", block);
         match fs::read_to_string(path) {
-            Ok(b) => match properties::RawBlockProperties::from_str(b.as_str()) {
+            Ok(b) => match properties::BlockFile::from_str(b.as_str())
+                .map_err(|e| e.to_string())
+                .and_then(|file| file.into_raw(&blocks))
+            {
                 Ok(raw) => {
                     assert_eq!(
                         file.write(&raw.to_bytes())
@@ -35,7 +40,7 @@ pub fn make_block_meta_file<T: Iterator<Item: AsRef<str>>>(iter: T) {
                     );
                 }
                 Err(e) => {
-                    eprintln!("Failed to parse block meta for {}: {}", block.as_ref(), e);
+                    eprintln!("Failed to parse block meta for {}: {}", block, e);
                     assert_eq!(
                         file.write(&RawBlockProperties::VOID.to_bytes())
                             .expect("Failed to write block meta"),
@@ -44,7 +49,7 @@ pub fn make_block_meta_file<T: Iterator<Item: AsRef<str>>>(iter: T) {
                 }
             },
             Err(e) => {
-                eprintln!("Failed to read block meta for {}: {}", block.as_ref(), e);
+                eprintln!("Failed to read block meta for {}: {}", block, e);
                 assert_eq!(
                     file.write(&RawBlockProperties::VOID.to_bytes())
                         .expect("Failed to write block meta"),
@@ -66,6 +71,9 @@ const RAW_DATA_LEN: usize = include_bytes!("../../../assets/blocks/raw.meta").le
 const THERMAL_CONDUCTIVITY: [FixedNum; (META_LEN * (META_LEN + 1)) / 2] =
     generate_thermal_conductivity();
 
+const ELECTRICAL_CONDUCTIVITY: [FixedNum; (META_LEN * (META_LEN + 1)) / 2] =
+    generate_electrical_conductivity();
+
 const RAW_SIZE: usize = size_of::<properties::RawBlockProperties>();
 
 pub const fn block_properties(block: u8) -> &'static BlockProperties {
@@ -123,6 +131,35 @@ const fn generate_thermal_conductivity() -> [FixedNum; (META_LEN * (META_LEN + 1
     conductivity
 }
 
+const fn generate_electrical_conductivity() -> [FixedNum; (META_LEN * (META_LEN + 1)) / 2] {
+    let mut conductivity = [FixedNum::ZERO; (META_LEN * (META_LEN + 1)) / 2];
+    let mut i = 0;
+    while i < META_LEN {
+        let mut j = i;
+        while j < META_LEN {
+            let a = block_properties(i as u8).electrical_conductivity;
+            let b = block_properties(j as u8).electrical_conductivity;
+            // if either side is an insulator no charge can flow
+            if a.to_bits() != 0 && b.to_bits() != 0 {
+                let hm = a
+                    .saturating_mul(b)
+                    .saturating_mul(FixedNum::const_from_int(2))
+                    .saturating_div(a.saturating_add(b));
+                // squash into 0..1 so one step can never move more charge then the difference
+                conductivity[((j * (j + 1)) / 2) + i] =
+                    hm.saturating_div(hm.saturating_add(E_REFERENCE));
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    conductivity
+}
+
+/// conductivity that will let half the charge difference flow in one step
+/// MS/m
+const E_REFERENCE: FixedNum = FixedNum::const_from_int(10);
+
 const ONETHOUSAND: FixedNum = FixedNum::const_from_int(1000);
 const ONEHUNDRED: FixedNum = FixedNum::const_from_int(100);
 const TEN: FixedNum = FixedNum::const_from_int(10);
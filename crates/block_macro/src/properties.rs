@@ -25,6 +25,99 @@ pub(super) struct RawBlockProperties {
     /// The Temperature at which the Voxel vaporizes
     /// Kelvin
     pub boiling_point: i32,
+    /// How easily charge flows through the Voxel
+    /// MS/m * 1000
+    pub electrical_conductivity: i32,
+    /// The stress a solid Voxel can take before it ruptures
+    /// kPa; 0 if it never breaks
+    pub strength: i32,
+    /// The Energy the Voxel gives off every TimeStep
+    /// J / Voxel / TimeStep
+    pub generating: i32,
+    /// TimeSteps until half of the Voxels have decayed
+    /// 0 if the Voxel is stable
+    pub half_life: i32,
+    /// The id of the block the Voxel decays into
+    /// named in the `.block` file, see `BlockFile`
+    pub decay_product: i32,
+    /// Fraction of the neutron flux the Voxel absorbs every TimeStep
+    /// 1 / 1000
+    pub absorption: i32,
+    /// Extra chance flux leaving the Voxel has to split fuel
+    /// 1 / 1000
+    pub moderation: i32,
+    /// Neutrons released for every neutron a fuel Voxel absorbs
+    /// 1 / 1000; 0 if the Voxel can't split
+    pub fission_yield: i32,
+    /// The Energy released for every neutron that splits the Voxel
+    /// J / neutron
+    pub fission_heat: i32,
+    /// Neutrons the Voxel gives off on its own every TimeStep
+    /// neutrons / Voxel / TimeStep
+    pub neutron_source: i32,
+}
+
+/// A `.block` file as it's written, same fields as `RawBlockProperties`
+/// but blocks are named so adding a block never renumbers the files
+#[derive(serde::Deserialize)]
+pub(super) struct BlockFile {
+    density: i32,
+    specific_heat: i32,
+    thermal_conductivity: i32,
+    fusion_energy: i32,
+    melting_point: i32,
+    vaporization_energy: i32,
+    boiling_point: i32,
+    electrical_conductivity: i32,
+    strength: i32,
+    generating: i32,
+    half_life: i32,
+    /// name of the block, only needed if `half_life` isn't 0
+    #[serde(default)]
+    decay_product: String,
+    absorption: i32,
+    moderation: i32,
+    fission_yield: i32,
+    fission_heat: i32,
+    neutron_source: i32,
+}
+
+impl BlockFile {
+    pub fn from_str(s: &str) -> SpannedResult<Self> {
+        ron::from_str(s)
+    }
+
+    /// `blocks` are the block names in id order
+    pub fn into_raw<T: AsRef<str>>(self, blocks: &[T]) -> Result<RawBlockProperties, String> {
+        let decay_product = if self.decay_product.is_empty() {
+            0
+        } else {
+            blocks
+                .iter()
+                .position(|block| block.as_ref() == self.decay_product)
+                .ok_or_else(|| format!("No block called {} to decay into", self.decay_product))?
+                as i32
+        };
+        Ok(RawBlockProperties {
+            density: self.density,
+            specific_heat: self.specific_heat,
+            thermal_conductivity: self.thermal_conductivity,
+            fusion_energy: self.fusion_energy,
+            melting_point: self.melting_point,
+            vaporization_energy: self.vaporization_energy,
+            boiling_point: self.boiling_point,
+            electrical_conductivity: self.electrical_conductivity,
+            strength: self.strength,
+            generating: self.generating,
+            half_life: self.half_life,
+            decay_product,
+            absorption: self.absorption,
+            moderation: self.moderation,
+            fission_yield: self.fission_yield,
+            fission_heat: self.fission_heat,
+            neutron_source: self.neutron_source,
+        })
+    }
 }
 
 impl RawBlockProperties {
@@ -38,10 +131,6 @@ impl RawBlockProperties {
         unsafe { std::mem::transmute(self) }
     }
 
-    pub fn from_str(s: &str) -> SpannedResult<Self> {
-        ron::from_str(s)
-    }
-
     pub const VOID: Self = RawBlockProperties {
         density: 0,
         specific_heat: 1000,
@@ -50,6 +139,16 @@ impl RawBlockProperties {
         melting_point: 0,
         vaporization_energy: 0,
         boiling_point: 0,
+        electrical_conductivity: 0,
+        strength: 0,
+        generating: 0,
+        half_life: 0,
+        decay_product: 0,
+        absorption: 1000,
+        moderation: 0,
+        fission_yield: 0,
+        fission_heat: 0,
+        neutron_source: 0,
     };
 }
 
@@ -77,6 +176,34 @@ pub struct BlockProperties {
     /// The Temperature at which the Voxel vaporizes
     /// Kelvin
     pub boiling_point: FixedNum,
+    /// How easily charge flows through the Voxel
+    /// MS/m
+    pub electrical_conductivity: FixedNum,
+    /// The stress a solid Voxel can take before it ruptures
+    /// kPa
+    /// MAX if it never breaks
+    pub strength: FixedNum,
+    /// The Energy the Voxel gives off every TimeStep
+    /// J / Voxel / TimeStep
+    pub generating: FixedNum,
+    /// TimeSteps until half of the Voxels have decayed
+    /// 0 if the Voxel is stable
+    pub half_life: u32,
+    /// The id of the block the Voxel decays into
+    pub decay_product: u8,
+    /// Fraction of the neutron flux the Voxel absorbs every TimeStep
+    pub absorption: FixedNum,
+    /// Extra chance flux leaving the Voxel has to split fuel
+    pub moderation: FixedNum,
+    /// Neutrons released for every neutron a fuel Voxel absorbs
+    /// 0 if the Voxel can't split
+    pub fission_yield: FixedNum,
+    /// The Energy released for every neutron that splits the Voxel
+    /// J / neutron
+    pub fission_heat: FixedNum,
+    /// Neutrons the Voxel gives off on its own every TimeStep
+    /// neutrons / Voxel / TimeStep
+    pub neutron_source: FixedNum,
 }
 
 impl BlockProperties {
@@ -86,6 +213,11 @@ impl BlockProperties {
         } else {
             FixedNum::const_from_int(raw.vaporization_energy)
         };
+        let strength = if raw.strength == 0 {
+            FixedNum::MAX
+        } else {
+            FixedNum::const_from_int(raw.strength)
+        };
 
         BlockProperties {
             density: FixedNum::const_from_int(raw.density),
@@ -96,6 +228,17 @@ impl BlockProperties {
             melting_point: FixedNum::const_from_int(raw.melting_point).saturating_div(ONEHUNDRED),
             vaporization_energy: ve,
             boiling_point: FixedNum::const_from_int(raw.boiling_point).saturating_div(ONEHUNDRED),
+            electrical_conductivity: FixedNum::const_from_int(raw.electrical_conductivity)
+                .saturating_div(ONETHOUSAND),
+            strength,
+            generating: FixedNum::const_from_int(raw.generating),
+            half_life: raw.half_life as u32,
+            decay_product: raw.decay_product as u8,
+            absorption: FixedNum::const_from_int(raw.absorption).saturating_div(ONETHOUSAND),
+            moderation: FixedNum::const_from_int(raw.moderation).saturating_div(ONETHOUSAND),
+            fission_yield: FixedNum::const_from_int(raw.fission_yield).saturating_div(ONETHOUSAND),
+            fission_heat: FixedNum::const_from_int(raw.fission_heat),
+            neutron_source: FixedNum::const_from_int(raw.neutron_source),
         }
     }
 
@@ -107,5 +250,21 @@ impl BlockProperties {
         melting_point: FixedNum::ZERO,
         vaporization_energy: FixedNum::ZERO,
         boiling_point: FixedNum::ZERO,
+        electrical_conductivity: FixedNum::ZERO,
+        strength: FixedNum::MAX,
+        generating: FixedNum::ZERO,
+        half_life: 0,
+        decay_product: 0,
+        absorption: FixedNum::ONE,
+        moderation: FixedNum::ZERO,
+        fission_yield: FixedNum::ZERO,
+        fission_heat: FixedNum::ZERO,
+        neutron_source: FixedNum::ZERO,
     };
+
+    /// Average TimeSteps a Voxel lasts before it decays
+    /// `half_life / ln(2)`; 0 if the Voxel is stable
+    pub const fn mean_life(&self) -> u32 {
+        ((self.half_life as u64 * 14427) / 10000) as u32
+    }
 }
@@ -0,0 +1,19 @@
+/// CRC-32 (the one zip and png use) of `data`
+pub fn checksum(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[test]
+fn checksum_matches_crc32() {
+    assert_eq!(checksum(b""), 0);
+    assert_eq!(checksum(b"123456789"), 0xCBF4_3926);
+    assert_ne!(checksum(b"PhoxW"), checksum(b"PhoxC"));
+}
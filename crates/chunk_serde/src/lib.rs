@@ -5,6 +5,11 @@ use std::fmt::Write;
 use bevy_ecs::error::BevyError;
 use bevy_ecs::error::Result;
 
+mod checksum;
+mod migrate;
+pub use checksum::checksum;
+pub use migrate::{Migration, Migrations, Palette};
+
 pub struct BinSerializer {
     index: usize,
     data: Vec<u8>,
@@ -15,8 +20,8 @@ pub struct BinDeSerializer<'a> {
     data: &'a [u8],
 }
 
-impl BinDeSerializer<'_> {
-    pub fn new(data: &[u8]) -> BinDeSerializer {
+impl<'a> BinDeSerializer<'a> {
+    pub fn new(data: &'a [u8]) -> BinDeSerializer<'a> {
         BinDeSerializer { index: 0, data }
     }
 
@@ -28,11 +33,34 @@ impl BinDeSerializer<'_> {
         self.data.is_empty()
     }
 
+    /// bytes left to extract
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.index
+    }
+
+    /// bytes extracted so far
+    pub fn offset(&self) -> usize {
+        self.index
+    }
+
+    /// errors come back as `BinError::AtOffset` so you can tell where it broke
     pub fn extract<T: Serialize>(&mut self) -> Result<T> {
-        let (v, used) = T::extract(&self.data[self.index..])?;
+        let Some(rest) = self.data.get(self.index..) else {
+            return Err(BinError::EOF.at(self.index).into());
+        };
+        let (v, used) = T::extract(rest).map_err(|e| BinError::wrap(e, self.index))?;
         self.index += used;
         Ok(v)
     }
+
+    /// Take the next `len` bytes as they are
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let Some(bytes) = self.data.get(self.index..self.index.saturating_add(len)) else {
+            return Err(BinError::EOF.at(self.index).into());
+        };
+        self.index += len;
+        Ok(bytes)
+    }
 }
 
 impl std::ops::Index<usize> for BinSerializer {
@@ -65,6 +93,10 @@ impl BinSerializer {
     pub fn push(&mut self, byte: u8) {
         self.data.push(byte);
     }
+
+    pub fn push_slice(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
     pub fn finalize(self) -> Vec<u8> {
         self.data
     }
@@ -104,21 +136,66 @@ pub struct StrSerializer {
     data: String,
 }
 
+impl Default for StrSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<String> for StrSerializer {
+    fn from(data: String) -> Self {
+        StrSerializer { index: 0, data }
+    }
+}
+
 impl StrSerializer {
+    pub fn new() -> StrSerializer {
+        StrSerializer {
+            index: 0,
+            data: String::new(),
+        }
+    }
+    pub fn finalize(self) -> String {
+        self.data
+    }
     pub fn len(&self) -> usize {
         self.data.len()
     }
     pub fn in_empty(&self) -> bool {
         self.data.is_empty()
     }
+    /// nothing but whitespace left to extract
+    pub fn is_finished(&self) -> bool {
+        self.data[self.index..].trim().is_empty()
+    }
     pub fn insert<T: Serialize>(&mut self, val: &T) -> Result<usize> {
         val.insert_str(self)
     }
+    /// bytes extracted so far
+    pub fn offset(&self) -> usize {
+        self.index
+    }
+    /// errors come back as `StrError::AtOffset` so you can tell where it broke
     pub fn extract<T: Serialize>(&mut self) -> Result<T> {
-        let (v, used) = T::extract_str(&self.data[self.index..])?;
+        let (v, used) =
+            T::extract_str(&self.data[self.index..]).map_err(|e| StrError::wrap(e, self.index))?;
         self.index += used;
         Ok(v)
     }
+    /// Skip whitespace then take `ch`
+    pub fn expect(&mut self, ch: char) -> Result<()> {
+        self.index +=
+            expect_char(&self.data[self.index..], ch).map_err(|e| StrError::wrap(e, self.index))?;
+        Ok(())
+    }
+    /// Skip whitespace then take a name or number
+    pub fn word(&mut self) -> Result<String> {
+        let (word, used) =
+            take_word(&self.data[self.index..]).map_err(|e| StrError::wrap(e, self.index))?;
+        let word = word.to_string();
+        self.index += used;
+        Ok(word)
+    }
     pub fn push(&mut self, ch: char) {
         self.data.push(ch);
     }
@@ -132,6 +209,53 @@ impl StrSerializer {
     }
 }
 
+/// Bytes of whitespace at the start of `str`
+pub fn skip_whitespace(str: &str) -> usize {
+    str.len() - str.trim_start().len()
+}
+
+/// Skip whitespace then expect `ch`, returns the bytes used
+pub fn expect_char(str: &str, ch: char) -> Result<usize> {
+    let skip = skip_whitespace(str);
+    match str[skip..].chars().next() {
+        Some(c) if c == ch => Ok(skip + c.len_utf8()),
+        Some(c) => Err(StrError::WrongChar(ch, c).into()),
+        None => Err(StrError::ExpectChar(ch).into()),
+    }
+}
+
+/// Skip whitespace then take a run of letters, digits, `_`, `.` and `-`
+/// returns the word and the bytes used
+pub fn take_word(str: &str) -> Result<(&str, usize)> {
+    let skip = skip_whitespace(str);
+    let rest = &str[skip..];
+    let len = rest
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-')))
+        .unwrap_or(rest.len());
+    if len == 0 {
+        return Err(match rest.chars().next() {
+            Some(c) => StrError::ExpectWord(c).into(),
+            None => StrError::EOF.into(),
+        });
+    }
+    Ok((&rest[..len], skip + len))
+}
+
+/// Write anything `Display`, returns the bytes written
+fn insert_display(val: &impl std::fmt::Display, serializer: &mut StrSerializer) -> Result<usize> {
+    let start = serializer.len();
+    serializer.write(format_args!("{val}"))?;
+    Ok(serializer.len() - start)
+}
+
+/// Skip whitespace then parse a whole number
+fn extract_int<T: std::str::FromStr<Err = std::num::ParseIntError>>(
+    str: &str,
+) -> Result<(T, usize)> {
+    let (word, used) = take_word(str)?;
+    Ok((word.parse::<T>().map_err(StrError::from)?, used))
+}
+
 pub trait Serialize: Sized {
     /// Add self to binarty serializer
     /// Returns the num bytes Added
@@ -142,17 +266,17 @@ pub trait Serialize: Sized {
     /// Add self to str serializer
     /// Returns the num char Added
     fn insert_str(&self, _serializer: &mut StrSerializer) -> Result<usize> {
-        unimplemented!()
+        Err(StrError::NoText(std::any::type_name::<Self>()).into())
     }
 
     /// Get self from str
     /// Returns the num char Used
     fn extract_str(_str: &str) -> Result<(Self, usize)> {
-        unimplemented!()
+        Err(StrError::NoText(std::any::type_name::<Self>()).into())
     }
 }
 
-#[derive(Eq, Debug)]
+#[derive(Eq, Debug, Clone)]
 pub enum CompressedChunkData<T> {
     Solid(T),
     RunLen(Vec<(T, u16)>),
@@ -160,6 +284,34 @@ pub enum CompressedChunkData<T> {
     Error(u8),
 }
 
+impl<T> CompressedChunkData<T> {
+    /// How many cells this expands to, `None` for a solid chunk since that fills any size
+    pub fn cell_count(&self) -> Option<usize> {
+        match self {
+            CompressedChunkData::Solid(_) => None,
+            CompressedChunkData::RunLen(runs) => {
+                Some(runs.iter().map(|(_, len)| *len as usize).sum())
+            }
+            CompressedChunkData::Raw(items) => Some(items.len()),
+            CompressedChunkData::Error(_) => Some(0),
+        }
+    }
+
+    /// Convert every cell, keeping the same compression
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> CompressedChunkData<U> {
+        match self {
+            CompressedChunkData::Solid(v) => CompressedChunkData::Solid(f(v)),
+            CompressedChunkData::RunLen(runs) => {
+                CompressedChunkData::RunLen(runs.into_iter().map(|(v, len)| (f(v), len)).collect())
+            }
+            CompressedChunkData::Raw(items) => {
+                CompressedChunkData::Raw(items.into_iter().map(f).collect())
+            }
+            CompressedChunkData::Error(i) => CompressedChunkData::Error(i),
+        }
+    }
+}
+
 impl<T: Eq> PartialEq for CompressedChunkData<T> {
     fn eq(&self, other: &Self) -> bool {
         match self {
@@ -205,11 +357,12 @@ impl<T: Serialize> Serialize for Vec<T> {
         let mut len = 0usize.to_be_bytes();
         let mut used = 0;
         for byte in len.iter_mut() {
-            *byte = slice[used];
+            *byte = *slice.get(used).ok_or(BinError::EOF)?;
             used += 1;
         }
         let len = usize::from_be_bytes(len);
-        let mut out = Vec::with_capacity(len);
+        // every item is at least a byte, so a bad length can't ask for more than is left
+        let mut out = Vec::with_capacity(len.min(slice.len() - used));
         for _ in 0..len {
             let (t, con) = T::extract(&slice[used..])?;
             out.push(t);
@@ -219,7 +372,7 @@ impl<T: Serialize> Serialize for Vec<T> {
     }
 
     fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
-        let mut used = 2;
+        let start = serializer.len();
         serializer.push('[');
         serializer.push('\n');
         let mut trailing = false;
@@ -227,54 +380,34 @@ impl<T: Serialize> Serialize for Vec<T> {
             if trailing {
                 serializer.push(',');
                 serializer.push('\n');
-                used += 2;
             } else {
                 trailing = true;
             }
-            used += val.insert_str(serializer)?;
+            val.insert_str(serializer)?;
         }
-        used += 2;
         serializer.push_str("\n]");
-        Ok(used)
+        Ok(serializer.len() - start)
     }
 
     fn extract_str(str: &str) -> Result<(Self, usize)> {
-        let mut used = 0;
-        let mut skip = 0;
-        let mut out = None;
-        let mut exp = '[';
-        for char in str.chars() {
-            used += 1;
-            if skip > 0 {
-                skip -= 1;
-                continue;
-            }
-            if char.is_whitespace() {
-                continue;
-            }
-            if char == '[' && out.is_none() {
-                let mut var = Vec::new();
-                let (res, len) = T::extract_str(&str[used..])?;
-                var.push(res);
-                skip += len;
-                out = Some(var);
-                exp = ']';
-                continue;
-            }
-            if char == ']' && out.is_some() {
-                break;
-            }
-            if char == ',' && out.is_none() {
-                if let Some(ref mut out) = out {
-                    let (res, len) = T::extract_str(&str[used..])?;
-                    out.push(res);
-                    skip += len;
-                    continue;
-                }
+        let mut used = expect_char(str, '[')?;
+        let mut out = Vec::new();
+        loop {
+            // empty list or a trailing comma
+            if let Ok(len) = expect_char(&str[used..], ']') {
+                return Ok((out, used + len));
+            }
+            let (val, len) = T::extract_str(&str[used..])?;
+            out.push(val);
+            used += len;
+            let skip = skip_whitespace(&str[used..]);
+            match str[used + skip..].chars().next() {
+                Some(',') => used += skip + 1,
+                Some(']') => return Ok((out, used + skip + 1)),
+                Some(c) => return Err(StrError::WrongChar(']', c).into()),
+                None => return Err(StrError::ExpectChar(']').into()),
             }
-            Err(StrError::WrongChar(exp, char))?;
         }
-        Ok((out.ok_or(StrError::EOF)?, used))
     }
 }
 
@@ -288,55 +421,27 @@ impl<T: Serialize> Serialize for (T, u16) {
     }
     fn extract(slice: &[u8]) -> Result<(Self, usize)> {
         let (t, at) = T::extract(slice)?;
-        Ok(((t, u16::from_be_bytes([slice[at], slice[at + 1]])), at + 2))
+        let (len, used) = u16::extract(&slice[at..])?;
+        Ok(((t, len), at + used))
     }
     fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
         let start = serializer.len();
         serializer.push('(');
         self.0.insert_str(serializer)?;
         serializer.push(',');
-        serializer.write(format_args!("{}", self.1));
+        serializer.write(format_args!("{}", self.1))?;
         serializer.push(')');
         Ok(serializer.len() - start)
     }
     fn extract_str(str: &str) -> Result<(Self, usize)> {
-        let mut used = 0;
-        let mut out = None;
-        let mut expt = '(';
-        let mut len = None;
-        let mut skip = 0;
-        for char in str.chars() {
-            used += 1;
-            if skip > 0 {
-                skip -= 1;
-                continue;
-            }
-            if char.is_whitespace() {
-                continue;
-            }
-            if char == '(' && out.is_none() {
-                let (res, con) = T::extract_str(&str[used..])?;
-                out = Some(res);
-                skip += con;
-                expt = ',';
-                continue;
-            }
-            if char == ',' && out.is_some() && len.is_none() {
-                let (res, con) = u16::extract_str(&str[used..])?;
-                len = Some(res);
-                skip += con;
-                expt = ')';
-            }
-            if char == ')' && len.is_some() {
-                break;
-            }
-            return Err(StrError::WrongChar(expt, char))?;
-        }
-        match (out, len) {
-            (Some(out), Some(len)) => Ok(((out, len), used)),
-            (None, None) | (None, Some(_)) => Err(StrError::TupleError(0))?,
-            (Some(_), None) => Err(StrError::TupleError(1))?,
-        }
+        let mut used = expect_char(str, '(')?;
+        let (out, len) = T::extract_str(&str[used..]).map_err(|_| StrError::TupleError(0))?;
+        used += len;
+        used += expect_char(&str[used..], ',')?;
+        let (count, len) = u16::extract_str(&str[used..]).map_err(|_| StrError::TupleError(1))?;
+        used += len;
+        used += expect_char(&str[used..], ')')?;
+        Ok(((out, count), used))
     }
 }
 
@@ -380,13 +485,74 @@ impl Serialize for u16 {
     }
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone)]
 pub enum BinError {
     #[error("EOF")]
     EOF,
+    #[error("Save is version {0} but only up to {1} is supported")]
+    FutureVersion(u16, u16),
+    #[error("Unknown name in palette: {0}")]
+    UnknownName(String),
+    #[error("Name is too long for palette: {0}")]
+    NameTooLong(String),
+    #[error("Invalid tag: {0}")]
+    InvalidTag(u8),
+    #[error("Invalid id: {0}")]
+    InvalidId(u8),
+    #[error("Checksum does not match")]
+    ChecksumMismatch,
+    #[error("Chunk has {0} cells, expected {1}")]
+    CellCount(usize, usize),
+    #[error("{0} has no text format")]
+    NoText(&'static str),
+    #[error("{error} at byte {offset}")]
+    AtOffset { offset: usize, error: Box<BinError> },
+}
+
+impl BinError {
+    /// an error that already has an offset is from a nested read, so `offset` is where that started
+    pub fn at(self, offset: usize) -> BinError {
+        match self {
+            BinError::AtOffset {
+                offset: inner,
+                error,
+            } => BinError::AtOffset {
+                offset: offset + inner,
+                error,
+            },
+            error => BinError::AtOffset {
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Where it broke if we know
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            BinError::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The error without its offset
+    pub fn root(&self) -> &BinError {
+        match self {
+            BinError::AtOffset { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// add `offset` to a `BinError` hiding in `error`, anything else is left alone
+    fn wrap(error: BevyError, offset: usize) -> BevyError {
+        match error.downcast_ref::<BinError>() {
+            Some(inner) => inner.clone().at(offset).into(),
+            None => error,
+        }
+    }
 }
 
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone)]
 pub enum StrError {
     #[error("EOF")]
     EOF,
@@ -402,6 +568,55 @@ pub enum StrError {
     InValidName(String, &'static [&'static str]),
     #[error("Missing Char: Expected {0} before EOF")]
     ExpectChar(char),
+    #[error("Expected a name or number found {0}")]
+    ExpectWord(char),
+    #[error("{0} has no text format")]
+    NoText(&'static str),
+    #[error("{error} at byte {offset}")]
+    AtOffset { offset: usize, error: Box<StrError> },
+}
+
+impl StrError {
+    /// an error that already has an offset is from a nested read, so `offset` is where that started
+    pub fn at(self, offset: usize) -> StrError {
+        match self {
+            StrError::AtOffset {
+                offset: inner,
+                error,
+            } => StrError::AtOffset {
+                offset: offset + inner,
+                error,
+            },
+            error => StrError::AtOffset {
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Where it broke if we know
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            StrError::AtOffset { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// The error without its offset
+    pub fn root(&self) -> &StrError {
+        match self {
+            StrError::AtOffset { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// add `offset` to a `StrError` hiding in `error`, anything else is left alone
+    fn wrap(error: BevyError, offset: usize) -> BevyError {
+        match error.downcast_ref::<StrError>() {
+            Some(inner) => inner.clone().at(offset).into(),
+            None => error,
+        }
+    }
 }
 
 impl<T: Eq + Serialize> Serialize for CompressedChunkData<T> {
@@ -432,7 +647,10 @@ impl<T: Eq + Serialize> Serialize for CompressedChunkData<T> {
     }
 
     fn extract(slice: &[u8]) -> Result<(Self, usize)> {
-        match slice[0] {
+        let Some(&tag) = slice.first() else {
+            return Err(BinError::EOF.into());
+        };
+        match tag {
             0 => {
                 let (out, used) = T::extract(&slice[1..])?;
                 Ok((CompressedChunkData::Solid(out), used + 1))
@@ -445,71 +663,61 @@ impl<T: Eq + Serialize> Serialize for CompressedChunkData<T> {
                 let (out, used) = Vec::extract(&slice[1..])?;
                 Ok((CompressedChunkData::Raw(out), used + 1))
             }
-            i => Ok((CompressedChunkData::Error(i), 1)),
+            i => Err(BinError::InvalidTag(i).into()),
         }
     }
 
     fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
-        let mut used = 0;
+        let start = serializer.len();
         match self {
             CompressedChunkData::Solid(v) => {
-                used += 7;
                 serializer.push_str("Solid(");
-                used += v.insert_str(serializer)?;
-                serializer.push(')');
+                v.insert_str(serializer)?;
             }
             CompressedChunkData::RunLen(items) => {
-                used += 8;
                 serializer.push_str("RunLen(");
-                used += items.insert_str(serializer)?;
-                serializer.push(')');
+                items.insert_str(serializer)?;
             }
             CompressedChunkData::Raw(items) => {
-                used += 5;
                 serializer.push_str("Raw(");
-                used += items.insert_str(serializer)?;
-                serializer.push(')');
+                items.insert_str(serializer)?;
             }
             CompressedChunkData::Error(_) => {
                 debug_assert!(false, "Don't Serialize Errors");
-                used += 7;
-                serializer.push_str("Error()");
+                serializer.push_str("Error(");
             }
         }
-        Ok(used)
+        serializer.push(')');
+        Ok(serializer.len() - start)
     }
 
     fn extract_str(str: &str) -> Result<(Self, usize)> {
-        let mut used = 0;
-        let mut target = String::new();
-        for char in str.chars() {
-            used += 1;
-            if char.is_whitespace() && target.is_empty() {
-                continue;
-            }
-            if char == '(' && !target.is_empty() {
-                return match target.as_str() {
-                    "Solid" => {
-                        let (res, len) = T::extract_str(&str[used..])?;
-                        used += len;
-                        Ok((CompressedChunkData::Solid(res), used))
-                    }
-                    "RunLen" => {
-                        let (res, len) = Vec::extract_str(&str[used..])?;
-                        used += len;
-                        Ok((CompressedChunkData::RunLen(res), used))
-                    }
-                    "Raw" => {
-                        let (res, len) = Vec::extract_str(&str[used..])?;
-                        used += len;
-                        Ok((CompressedChunkData::Raw(res), used))
-                    }
-                    _ => Err(StrError::InValidName(target, &["Solid", "RunLen", "Raw"]).into()),
-                };
-            }
-            target.push(char);
-        }
-        Err(StrError::EOF.into())
+        let (name, mut used) = take_word(str)?;
+        used += expect_char(&str[used..], '(')?;
+        let out = match name {
+            "Solid" => {
+                let (res, len) = T::extract_str(&str[used..])?;
+                used += len;
+                CompressedChunkData::Solid(res)
+            }
+            "RunLen" => {
+                let (res, len) = Vec::extract_str(&str[used..])?;
+                used += len;
+                CompressedChunkData::RunLen(res)
+            }
+            "Raw" => {
+                let (res, len) = Vec::extract_str(&str[used..])?;
+                used += len;
+                CompressedChunkData::Raw(res)
+            }
+            _ => {
+                return Err(
+                    StrError::InValidName(name.to_string(), &["Solid", "RunLen", "Raw"]).into(),
+                );
+            }
+        };
+        used += expect_char(&str[used..], ')')?;
+        Ok((out, used))
     }
 }
 
@@ -530,6 +738,14 @@ impl Serialize for i32 {
         let bytes = [slice[0], slice[1], slice[2], slice[3]];
         Ok((i32::from_be_bytes(bytes), 4))
     }
+
+    fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
+        insert_display(self, serializer)
+    }
+
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        extract_int(str)
+    }
 }
 
 #[test]
@@ -561,6 +777,29 @@ fn test_i32_serialize() {
     assert_eq!(extracted_value, 12345678);
 }
 
+impl Serialize for u32 {
+    fn insert(&self, serializer: &mut BinSerializer) -> Result<usize> {
+        serializer.push_slice(&self.to_be_bytes());
+        Ok(4)
+    }
+
+    fn extract(slice: &[u8]) -> Result<(Self, usize)> {
+        if slice.len() < 4 {
+            Err(BinError::EOF)?
+        }
+        let bytes = [slice[0], slice[1], slice[2], slice[3]];
+        Ok((u32::from_be_bytes(bytes), 4))
+    }
+
+    fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
+        insert_display(self, serializer)
+    }
+
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        extract_int(str)
+    }
+}
+
 impl Serialize for u64 {
     fn insert(&self, serializer: &mut BinSerializer) -> Result<usize> {
         let bytes = self.to_be_bytes();
@@ -578,6 +817,14 @@ impl Serialize for u64 {
         bytes.copy_from_slice(&slice[..8]);
         Ok((u64::from_be_bytes(bytes), 8))
     }
+
+    fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
+        insert_display(self, serializer)
+    }
+
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        extract_int(str)
+    }
 }
 
 #[test]
@@ -650,3 +897,88 @@ fn test_array_serialize() {
     let extracted_value = de.extract::<[u8; 8]>().unwrap();
     assert_eq!(extracted_value, [9, 10, 11, 12, 1, 2, 3, 4]); // Padding for the array size
 }
+
+#[test]
+fn test_compressed_str_round_trip() {
+    let data = CompressedChunkData::RunLen(vec![(1u16, 4), (2, 996)]);
+    let mut serializer = StrSerializer::new();
+    serializer.insert(&data).unwrap();
+    let text = serializer.finalize();
+    let mut de = StrSerializer::from(text);
+    assert!(de.extract::<CompressedChunkData<u16>>().unwrap() == data);
+    assert!(de.is_finished());
+
+    let mut de = StrSerializer::from(" Raw([ 1, 2,\n 3, ]) ".to_string());
+    assert!(
+        de.extract::<CompressedChunkData<u16>>().unwrap()
+            == CompressedChunkData::Raw(vec![1, 2, 3])
+    );
+    assert!(
+        StrSerializer::from("Rle([])".to_string())
+            .extract::<CompressedChunkData<u16>>()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_errors_know_offset() {
+    let mut serializer = BinSerializer::new();
+    serializer.insert(&7u16).unwrap();
+    serializer.push(1);
+    let mut de = BinDeSerializer::new(serializer.as_ref());
+    de.extract::<u16>().unwrap();
+    let error = de.extract::<u16>().unwrap_err();
+    let error = error.downcast_ref::<BinError>().unwrap();
+    assert_eq!(error.offset(), Some(2));
+    assert!(matches!(error.root(), BinError::EOF));
+
+    let mut de = StrSerializer::from("a: b".to_string());
+    de.word().unwrap();
+    de.expect(':').unwrap();
+    let error = de.expect(':').unwrap_err();
+    let error = error.downcast_ref::<StrError>().unwrap();
+    assert_eq!(error.offset(), Some(2));
+    assert!(matches!(error.root(), StrError::WrongChar(':', 'b')));
+
+    // an error from inside something nested is counted from the start of the outer data
+    assert_eq!(BinError::EOF.at(3).at(10).offset(), Some(13));
+    assert_eq!(StrError::EOF.at(3).at(10).offset(), Some(13));
+}
+
+#[test]
+fn test_int_str_round_trip() {
+    let mut serializer = StrSerializer::new();
+    serializer.insert(&-12i32).unwrap();
+    serializer.push(' ');
+    serializer.insert(&u64::MAX).unwrap();
+    let mut de = StrSerializer::from(serializer.finalize());
+    assert_eq!(de.extract::<i32>().unwrap(), -12);
+    assert_eq!(de.extract::<u64>().unwrap(), u64::MAX);
+    assert!(
+        StrSerializer::from("x".to_string())
+            .extract::<u32>()
+            .is_err()
+    );
+}
+
+#[test]
+fn no_text_format_is_an_error() {
+    #[derive(Debug)]
+    struct BinOnly;
+    impl Serialize for BinOnly {
+        fn insert(&self, _serializer: &mut BinSerializer) -> Result<usize> {
+            Ok(0)
+        }
+        fn extract(_slice: &[u8]) -> Result<(Self, usize)> {
+            Ok((BinOnly, 0))
+        }
+    }
+    let error = StrSerializer::new().insert(&BinOnly).unwrap_err();
+    let error = error.downcast_ref::<StrError>().unwrap();
+    assert!(matches!(error.root(), StrError::NoText(_)));
+    let error = StrSerializer::from("x".to_string())
+        .extract::<BinOnly>()
+        .unwrap_err();
+    let error = error.downcast_ref::<StrError>().unwrap();
+    assert!(matches!(error.root(), StrError::NoText(_)));
+}
@@ -0,0 +1,128 @@
+use std::borrow::Cow;
+
+use bevy_ecs::error::Result;
+
+use crate::{BinError, BinSerializer, Serialize};
+
+/// Rewrites the body of a save from one version to the next
+pub type Migration = fn(&[u8]) -> Result<Vec<u8>>;
+
+/// Every step needed to bring an old save up to date,
+/// `steps[n]` takes version `n` to version `n + 1`
+pub struct Migrations {
+    steps: &'static [Migration],
+}
+
+impl Migrations {
+    pub const fn new(steps: &'static [Migration]) -> Migrations {
+        Migrations { steps }
+    }
+
+    /// the version new saves are written as
+    pub const fn current(&self) -> u16 {
+        self.steps.len() as u16
+    }
+
+    /// Run every step from `version` to current, borrows `body` if it's already current
+    pub fn upgrade<'a>(&self, version: u16, body: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        if version > self.current() {
+            Err(BinError::FutureVersion(version, self.current()))?
+        }
+        let mut body = Cow::Borrowed(body);
+        for step in &self.steps[version as usize..] {
+            body = Cow::Owned(step(&body)?);
+        }
+        Ok(body)
+    }
+}
+
+/// The name of every id used in a save,
+/// lets ids be reordered without breaking old saves
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Palette(Vec<String>);
+
+impl Palette {
+    pub fn new<S: AsRef<str>>(names: impl IntoIterator<Item = S>) -> Palette {
+        Palette(names.into_iter().map(|n| n.as_ref().to_string()).collect())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    /// Table to look up what an id in the save is now, by name
+    pub fn remap<T>(&self, find: impl Fn(&str) -> Option<T>) -> Result<Vec<T>> {
+        self.names()
+            .map(|name| find(name).ok_or_else(|| BinError::UnknownName(name.to_string()).into()))
+            .collect()
+    }
+}
+
+impl Serialize for Palette {
+    fn insert(&self, serializer: &mut BinSerializer) -> Result<usize> {
+        let mut used = (self.0.len() as u16).insert(serializer)?;
+        for name in &self.0 {
+            let Ok(len) = u8::try_from(name.len()) else {
+                return Err(BinError::NameTooLong(name.clone()).into());
+            };
+            serializer.push(len);
+            for byte in name.bytes() {
+                serializer.push(byte);
+            }
+            used += 1 + name.len();
+        }
+        Ok(used)
+    }
+
+    fn extract(slice: &[u8]) -> Result<(Self, usize)> {
+        let (len, mut used) = u16::extract(slice)?;
+        let mut names = Vec::with_capacity(len as usize);
+        for _ in 0..len {
+            let Some(&name_len) = slice.get(used) else {
+                return Err(BinError::EOF.into());
+            };
+            used += 1;
+            let Some(name) = slice.get(used..used + name_len as usize) else {
+                return Err(BinError::EOF.into());
+            };
+            names.push(String::from_utf8(name.to_vec())?);
+            used += name_len as usize;
+        }
+        Ok((Palette(names), used))
+    }
+}
+
+#[test]
+fn palette_round_trip() {
+    let palette = Palette::new(["Air", "Copper", "Iron"]);
+    let mut serializer = BinSerializer::new();
+    let used = palette.insert(&mut serializer).unwrap();
+    assert_eq!(used, serializer.len());
+    let (out, read) = Palette::extract(serializer.as_ref()).unwrap();
+    assert_eq!(out, palette);
+    assert_eq!(read, used);
+
+    let now = ["Iron", "Air", "Copper"];
+    let table = palette
+        .remap(|name| now.iter().position(|n| *n == name))
+        .unwrap();
+    assert_eq!(table, vec![1, 2, 0]);
+    assert!(Palette::new(["Gold"]).remap(|_| None::<u8>).is_err());
+}
+
+#[test]
+fn migrations_run_in_order() {
+    static STEPS: [Migration; 2] = [
+        |body| Ok([body, b"1"].concat()),
+        |body| Ok([body, b"2"].concat()),
+    ];
+    let migrations = Migrations::new(&STEPS);
+    assert_eq!(migrations.current(), 2);
+    assert_eq!(&*migrations.upgrade(0, b"0").unwrap(), b"012");
+    assert_eq!(&*migrations.upgrade(1, b"1").unwrap(), b"12");
+    assert!(matches!(
+        migrations.upgrade(2, b"2").unwrap(),
+        Cow::Borrowed(_)
+    ));
+    assert!(migrations.upgrade(3, b"3").is_err());
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    menu::MapSize,
+    saves::{SaveMeta, Saves},
+    voxels::{
+        ChunkManager,
+        cellular_automata::{ApplyStep, Cells, VoxelTick, WorldSeed, can_modify_world},
+    },
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Autosave>();
+    // once the tick has been applied and edited the world is whole until the next one starts
+    app.add_systems(
+        Update,
+        autosave
+            .after(ApplyStep::Edit)
+            .run_if(can_modify_world)
+            .run_if(in_state(GameState::Game)),
+    );
+}
+
+/// Snapshot the world every `every` ticks, keeping the last `keep` around to rewind to
+#[derive(Resource)]
+pub struct Autosave {
+    /// 0 turns autosave off
+    pub every: u64,
+    pub keep: usize,
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Autosave {
+            every: 1800, // once a minute at 1x
+            keep: 5,
+            snapshots: VecDeque::new(),
+        }
+    }
+}
+
+pub struct Snapshot {
+    pub tick: u64,
+    data: Vec<u8>,
+}
+
+impl Autosave {
+    /// newest first
+    pub fn snapshots(&self) -> impl Iterator<Item = &Snapshot> {
+        self.snapshots.iter().rev()
+    }
+
+    pub fn set_keep(&mut self, keep: usize) {
+        self.keep = keep;
+        self.trim();
+    }
+
+    fn push(&mut self, tick: u64, data: Vec<u8>) {
+        self.snapshots.push_back(Snapshot { tick, data });
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.snapshots.len() > self.keep {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Take the snapshot `back` steps from the newest,
+    /// anything newer is from a future that won't happen now so it goes too
+    pub fn rewind(&mut self, back: usize) -> Option<Vec<u8>> {
+        let index = self.snapshots.len().checked_sub(back + 1)?;
+        self.snapshots.truncate(index + 1);
+        self.snapshots.back().map(|s| s.data.clone())
+    }
+
+    fn last_tick(&self) -> Option<u64> {
+        self.snapshots.back().map(|s| s.tick)
+    }
+}
+
+impl Snapshot {
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+}
+
+fn autosave(
+    mut autosave: ResMut<Autosave>,
+    mut saves: ResMut<Saves>,
+    manager: Res<ChunkManager>,
+    chunks: Query<&Cells>,
+    tick: Res<VoxelTick>,
+    seed: Res<WorldSeed>,
+    map_size: Res<MapSize>,
+) {
+    let tick = tick.get();
+    if autosave.every == 0
+        || tick == 0
+        || tick % autosave.every != 0
+        || autosave.last_tick() == Some(tick)
+    {
+        return;
+    }
+    let data = match manager.save_world(&chunks, tick, seed.get()) {
+        Ok(data) => data,
+        Err(e) => {
+            error!("Failed to autosave: {e}");
+            return;
+        }
+    };
+    // newest one goes to disk too so it survives a crash
+    let meta = SaveMeta::new("auto", tick, map_size.0, chunks.iter());
+    if let Err(e) = saves.write(&data, &meta) {
+        error!("Failed to write autosave: {e}");
+    }
+    autosave.push(tick, data);
+    debug!("autosaved at tick {tick}");
+}
+
+#[test]
+fn rewind_drops_newer_snapshots() {
+    let mut autosave = Autosave {
+        keep: 3,
+        ..Default::default()
+    };
+    for tick in 1..=4 {
+        autosave.push(tick, vec![tick as u8]);
+    }
+    assert_eq!(
+        autosave.snapshots().map(|s| s.tick).collect::<Vec<_>>(),
+        [4, 3, 2]
+    );
+    assert_eq!(autosave.rewind(1), Some(vec![3]));
+    assert_eq!(autosave.last_tick(), Some(3));
+    assert_eq!(autosave.rewind(5), None);
+}
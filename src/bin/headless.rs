@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+use meltdown_manager::run_headless;
+
+const USAGE: &str = "usage: headless <INPUT> <OUTPUT> <TICKS>\n\
+    INPUT can be a world save, a text export or a recorded journal\n\
+    OUTPUT is written as a text export if it ends in .phoxt";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(input), Some(output), Some(ticks)) = (args.next(), args.next(), args.next()) else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+    let Ok(ticks) = ticks.parse::<u64>() else {
+        eprintln!("TICKS must be a number\n{USAGE}");
+        std::process::exit(2);
+    };
+    if let Err(e) = run_headless(&PathBuf::from(input), &PathBuf::from(output), ticks) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,62 @@
+use std::path::{Path, PathBuf};
+
+use meltdown_manager::{first_divergence, record_hashes};
+
+const USAGE: &str = "usage: replay <SAVE> <TICKS> [HASH_FILE]";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let (Some(save), Some(ticks)) = (args.next(), args.next()) else {
+        eprintln!("{USAGE}");
+        std::process::exit(2);
+    };
+    let Ok(ticks) = ticks.parse::<u64>() else {
+        eprintln!("TICKS must be a number\n{USAGE}");
+        std::process::exit(2);
+    };
+    let save = PathBuf::from(save);
+    let hash_file = args.next().map(PathBuf::from);
+
+    let run = record_hashes(&save, ticks).unwrap_or_else(|e| fail(e));
+    // with no reference to check against just replay it a second time
+    let expected = match &hash_file {
+        Some(path) if path.exists() => read_hashes(path),
+        Some(path) => {
+            let text = run
+                .iter()
+                .map(|(tick, hash)| format!("{tick} {hash:016x}\n"))
+                .collect::<String>();
+            std::fs::write(path, text).unwrap_or_else(|e| fail(e));
+            println!("recorded {} hashes to {}", run.len(), path.display());
+            return;
+        }
+        None => record_hashes(&save, ticks).unwrap_or_else(|e| fail(e)),
+    };
+
+    match first_divergence(&expected, &run) {
+        None => println!("deterministic over {} ticks", run.len()),
+        Some(tick) => {
+            println!("diverged at tick {tick}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// one `tick hash` pair per line, hash in hex
+fn read_hashes(path: &Path) -> Vec<(u64, u64)> {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| fail(e));
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (tick, hash) = line.split_once(' ').unwrap_or_else(|| fail(line));
+            let tick = tick.parse().unwrap_or_else(|_| fail(line));
+            let hash = u64::from_str_radix(hash.trim(), 16).unwrap_or_else(|_| fail(line));
+            (tick, hash)
+        })
+        .collect()
+}
+
+fn fail(e: impl std::fmt::Display) -> ! {
+    eprintln!("{e}");
+    std::process::exit(1);
+}
@@ -1,19 +1,58 @@
+pub use autosave::*;
+pub use blueprint::*;
+pub use breakpoint::*;
 pub use export::*;
 pub use highlight::*;
+pub use history::*;
+pub use journal::*;
 pub use neighbors::*;
+pub use prefab::*;
+pub use probe::*;
 pub use redraw::*;
 pub use save_load::*;
+pub use shapes::*;
+pub use slots::*;
+pub use speed::*;
+pub use stream::*;
+pub use text::*;
 
+mod autosave;
+mod blueprint;
+mod breakpoint;
 mod export;
 mod highlight;
+mod history;
+mod journal;
 mod neighbors;
+mod prefab;
+mod probe;
 mod redraw;
 mod save_load;
+mod shapes;
+mod slots;
+mod speed;
+mod stream;
+mod text;
+
+use bevy::prelude::*;
+use bevy_console::ConsoleCommand;
 
 use super::AxisPointer;
+use crate::MeltdownError;
 
 pub(super) fn init(app: &mut bevy::app::App) {
     highlight::init(app);
     neighbors::init(app);
     redraw::init(app);
 }
+
+/// Failures go to the log as well as the console so they read the same in both
+pub(crate) fn reply_error<T>(
+    log: &mut ConsoleCommand<T>,
+    what: &str,
+    error: impl Into<MeltdownError>,
+) {
+    let error = error.into();
+    warn!("{what}: {error}");
+    log.reply_failed(format!("{what}: {error}"));
+}
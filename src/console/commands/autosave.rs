@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use super::reply_error;
+use crate::autosave::Autosave;
+use crate::voxels::ChunkManager;
+
+/// Control how often the world is snapshotted
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "autosave")]
+pub enum AutosaveCommand {
+    /// snapshot every TICKS ticks, 0 to turn it off
+    Every {
+        #[arg(value_name = "TICKS")]
+        ticks: u64,
+    },
+    /// how many snapshots to keep
+    Keep {
+        #[arg(value_name = "COUNT")]
+        count: usize,
+    },
+    List,
+}
+
+/// Go back to an autosave, 0 is the newest
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "rewind")]
+pub struct RewindCommand {
+    #[arg(value_name = "BACK", default_value = "0")]
+    back: usize,
+}
+
+pub fn autosave_command(mut log: ConsoleCommand<AutosaveCommand>, mut autosave: ResMut<Autosave>) {
+    if let Some(Ok(c)) = log.take() {
+        match c {
+            AutosaveCommand::Every { ticks } => {
+                autosave.every = ticks;
+                if ticks == 0 {
+                    reply!(log, "Autosave off");
+                } else {
+                    reply!(log, "Autosaving every {} ticks", ticks);
+                }
+            }
+            AutosaveCommand::Keep { count } => {
+                autosave.set_keep(count);
+                reply!(log, "Keeping {} snapshots", count);
+            }
+            AutosaveCommand::List => {
+                if autosave.snapshots().next().is_none() {
+                    reply!(log, "No snapshots yet");
+                }
+                for (back, snapshot) in autosave.snapshots().enumerate() {
+                    reply!(
+                        log,
+                        "{}: tick {} ({} bytes)",
+                        back,
+                        snapshot.tick,
+                        snapshot.size()
+                    );
+                }
+            }
+        }
+    }
+}
+
+pub fn rewind_command(
+    mut log: ConsoleCommand<RewindCommand>,
+    mut autosave: ResMut<Autosave>,
+    manager: Res<ChunkManager>,
+    mut commands: Commands,
+) {
+    if let Some(Ok(RewindCommand { back })) = log.take() {
+        let Some(data) = autosave.rewind(back) else {
+            reply_failed!(log, "No snapshot {} back", back);
+            return;
+        };
+        match manager.load_world(&data, &mut commands) {
+            Ok(report) => reply!(log, "Rewound, {}", report),
+            Err(e) => reply_error(&mut log, "Failed to rewind", e),
+        }
+    }
+}
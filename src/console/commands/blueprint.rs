@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use super::reply_error;
+use crate::saves::check_name;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::voxels::AssetError;
+use crate::voxels::ChunkManager;
+use crate::voxels::cellular_automata::{Cells, PendingEdits};
+use crate::voxels::voxel_chunk::blueprint::{Blueprint, BlueprintError, Clipboard};
+
+/// Copy a box of the world and paste it somewhere else
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "blueprint")]
+pub enum BlueprintCommand {
+    /// copy every block between two corners into the clipboard
+    Copy {
+        x1: i32,
+        y1: i32,
+        z1: i32,
+        x2: i32,
+        y2: i32,
+        z2: i32,
+        /// keep the tempretures too
+        #[arg(long)]
+        tempretures: bool,
+    },
+    /// paste the clipboard with its min corner at X Y Z
+    Paste {
+        x: i32,
+        y: i32,
+        z: i32,
+        /// quarter turns about y
+        #[arg(value_name = "TURNS", default_value = "0")]
+        turns: u8,
+        /// flip along x before turning
+        #[arg(long)]
+        mirror: bool,
+    },
+    /// write the clipboard to assets/blueprints/NAME.blueprint
+    Save {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    /// load assets/blueprints/NAME.blueprint into the clipboard
+    Load {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+}
+
+pub fn blueprint_command(
+    mut log: ConsoleCommand<BlueprintCommand>,
+    mut clipboard: ResMut<Clipboard>,
+    manager: Res<ChunkManager>,
+    chunks: Query<&Cells>,
+    mut edits: ResMut<PendingEdits>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(Ok(c)) = log.take() else {
+        return;
+    };
+    match c {
+        BlueprintCommand::Copy {
+            x1,
+            y1,
+            z1,
+            x2,
+            y2,
+            z2,
+            tempretures,
+        } => {
+            let a = IVec3::new(x1, y1, z1);
+            let b = IVec3::new(x2, y2, z2);
+            match Blueprint::capture(a, b, tempretures, &manager, &chunks) {
+                Ok(blueprint) => {
+                    reply!(log, "Copied {}", blueprint.size());
+                    clipboard.blueprint = Some(blueprint);
+                }
+                Err(e) => reply_error(&mut log, "Failed to copy", e),
+            }
+        }
+        BlueprintCommand::Paste {
+            x,
+            y,
+            z,
+            turns,
+            mirror,
+        } => {
+            let Some(blueprint) = &clipboard.blueprint else {
+                reply_failed!(log, "Nothing to paste, copy or load a blueprint first");
+                return;
+            };
+            let dropped = blueprint.paste(IVec3::new(x, y, z), turns, mirror, &manager, &mut edits);
+            if dropped > 0 {
+                reply!(log, "Pasted, {} blocks were outside the world", dropped);
+            } else {
+                reply!(log, "Pasted");
+            }
+        }
+        BlueprintCommand::Save { name } => {
+            if let Err(e) = check_name(&name) {
+                reply_error(&mut log, "Failed to save blueprint", e);
+                return;
+            }
+            let Some(blueprint) = &clipboard.blueprint else {
+                reply_failed!(log, "Nothing to save, copy a blueprint first");
+                return;
+            };
+            match save(&name, blueprint) {
+                Ok(()) => reply!(log, "Saved blueprints/{}.blueprint", name),
+                Err(e) => reply_error(&mut log, "Failed to save blueprint", e),
+            }
+        }
+        BlueprintCommand::Load { name } => {
+            if let Err(e) = check_name(&name) {
+                reply_error(&mut log, "Failed to load blueprint", e);
+                return;
+            }
+            clipboard.load(asset_server.load(format!("blueprints/{name}.blueprint")));
+            reply!(log, "Loading {}", name);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save(name: &str, blueprint: &Blueprint) -> Result<(), BlueprintError> {
+    std::fs::create_dir_all("assets/blueprints").map_err(AssetError::from)?;
+    std::fs::write(
+        format!("assets/blueprints/{name}.blueprint"),
+        blueprint.to_text()?,
+    )
+    .map_err(AssetError::from)?;
+    Ok(())
+}
+
+// no assets folder to write to in a browser
+#[cfg(target_arch = "wasm32")]
+fn save(_name: &str, _blueprint: &Blueprint) -> Result<(), BlueprintError> {
+    Err(BlueprintError::NoFiles)
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use crate::voxels::BlockType;
+use crate::voxels::cellular_automata::{Breakpoint, Breakpoints, FixedNum};
+
+/// Pause the simulation on the tick something happens
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "break")]
+pub enum BreakCommand {
+    /// when any cell heats past KELVIN
+    Above {
+        #[arg(value_parser = super::shapes::kelvin)]
+        kelvin: FixedNum,
+    },
+    /// when a BLOCK cell turns solid, liquid or gas
+    Phase {
+        block: BlockType,
+    },
+    /// when anything about the cell at x y z changes
+    Cell {
+        x: i32,
+        y: i32,
+        z: i32,
+    },
+    /// show every breakpoint and what last set one off
+    List,
+    /// remove breakpoint N from the list
+    Remove {
+        n: usize,
+    },
+    Clear,
+}
+
+pub fn break_command(mut log: ConsoleCommand<BreakCommand>, mut breakpoints: ResMut<Breakpoints>) {
+    let Some(Ok(c)) = log.take() else {
+        return;
+    };
+    let breakpoint = match c {
+        BreakCommand::Above { kelvin } => Breakpoint::Above(kelvin),
+        BreakCommand::Phase { block } => Breakpoint::Phase(block),
+        BreakCommand::Cell { x, y, z } => Breakpoint::Cell(IVec3::new(x, y, z)),
+        BreakCommand::List => {
+            if breakpoints.is_empty() {
+                reply!(log, "No breakpoints set");
+            }
+            for (i, breakpoint) in breakpoints.iter().enumerate() {
+                reply!(log, "{}: {}", i, breakpoint);
+            }
+            for hit in &breakpoints.last_hits {
+                reply!(log, "last hit {}", hit);
+            }
+            return;
+        }
+        BreakCommand::Remove { n } => {
+            match breakpoints.remove(n) {
+                Some(removed) => reply!(log, "Removed {}", removed),
+                None => reply_failed!(log, "No breakpoint {}", n),
+            }
+            return;
+        }
+        BreakCommand::Clear => {
+            breakpoints.clear();
+            reply!(log, "Cleared breakpoints");
+            return;
+        }
+    };
+    reply!(log, "Pausing {}", breakpoint);
+    breakpoints.add(breakpoint);
+}
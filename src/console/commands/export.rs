@@ -2,6 +2,9 @@ use bevy::prelude::*;
 use bevy_console::reply;
 use bevy_console::{ConsoleCommand, clap::Parser, reply_failed};
 
+use super::reply_error;
+use crate::MeltdownError;
+use crate::saves::{SaveMeta, Saves, read_or_migrate};
 use crate::voxels::cellular_automata::Cells;
 use crate::voxels::{ChunkId, ChunkManager};
 
@@ -57,6 +60,7 @@ pub fn chunk_export_command(
     manager: Res<ChunkManager>,
     chunks: Query<&Cells>,
     store: Res<bevy_pkv::PkvStore>,
+    mut saves: ResMut<Saves>,
     tick: Res<crate::voxels::cellular_automata::VoxelTick>,
 ) {
     if let Some(Ok(c)) = log.take() {
@@ -66,7 +70,8 @@ pub fn chunk_export_command(
                 let data = match manager.save_compressed_chunk(chunk_id, &chunks) {
                     Ok(data) => data,
                     Err(e) => {
-                        reply_failed!(log, "Failed to save chunk: {}", e);
+                        let e = MeltdownError::from(e).in_chunk(chunk_id);
+                        reply_error(&mut log, "Failed to save chunk", e);
                         return;
                     }
                 };
@@ -83,7 +88,7 @@ pub fn chunk_export_command(
                 let data = match manager.save_compressed_world(&chunks, tick.get()) {
                     Ok(data) => data,
                     Err(e) => {
-                        reply_failed!(log, "Failed to save world: {}", e);
+                        reply_error(&mut log, "Failed to save world", e);
                         return;
                     }
                 };
@@ -91,10 +96,10 @@ pub fn chunk_export_command(
             }
             Export::Saved { file } => {
                 let path = if file.is_empty() { "auto" } else { &file };
-                let data = match store.get::<Vec<u8>>(path) {
+                let data = match read_or_migrate(&mut saves, &store, path) {
                     Ok(data) => data,
                     Err(e) => {
-                        reply_failed!(log, "Failed to retrieve data from store: {}", e);
+                        reply_error(&mut log, "Failed to read save", e);
                         return;
                     }
                 };
@@ -112,7 +117,7 @@ pub fn chunk_export_command(
 pub fn chunk_import_command(
     mut log: ConsoleCommand<Import>,
     manager: Res<ChunkManager>,
-    mut store: ResMut<bevy_pkv::PkvStore>,
+    mut saves: ResMut<Saves>,
     mut commands: Commands,
 ) {
     if let Some(Ok(c)) = log.take() {
@@ -130,12 +135,14 @@ pub fn chunk_import_command(
                     if let Err(e) =
                         manager.load_compressed_chunk(chunk_id, &decoded_data, &mut commands)
                     {
-                        reply_failed!(log, "Failed to load chunk: {}", e);
+                        let e = MeltdownError::from(e).in_chunk(chunk_id);
+                        reply_error(&mut log, "Failed to load chunk", e);
                     } else {
                         reply!(log, "Chunk ({}, {}, {}) loaded successfully.", x, y, z);
                     }
                 } else if let Err(e) = manager.load_chunk(chunk_id, &decoded_data, &mut commands) {
-                    reply_failed!(log, "Failed to load chunk: {}", e);
+                    let e = MeltdownError::from(e).in_chunk(chunk_id);
+                    reply_error(&mut log, "Failed to load chunk", e);
                 } else {
                     reply!(log, "Chunk ({}, {}, {}) loaded successfully.", x, y, z);
                 }
@@ -149,13 +156,15 @@ pub fn chunk_import_command(
                     }
                 };
                 if decoded_data.starts_with(b"PhoxW") {
-                    if let Err(e) = manager.load_world(&decoded_data, &mut commands) {
-                        reply_failed!(log, "Failed to load world: {}", e);
-                    } else {
-                        reply!(log, "World loaded successfully.");
+                    match manager.load_world(&decoded_data, &mut commands) {
+                        Ok(report) if report.is_clean() => {
+                            reply!(log, "World loaded successfully.")
+                        }
+                        Ok(report) => reply_failed!(log, "World is damaged, {}", report),
+                        Err(e) => reply_error(&mut log, "Failed to load world", e),
                     }
                 } else if let Err(e) = manager.load_compressed_world(&decoded_data, &mut commands) {
-                    reply_failed!(log, "Failed to load world: {}", e);
+                    reply_error(&mut log, "Failed to load world", e);
                 } else {
                     reply!(log, "World loaded successfully.");
                 }
@@ -182,10 +191,14 @@ pub fn chunk_import_command(
                     );
                     return;
                 }
-                if let Err(e) = store.set(path, &decoded_data) {
-                    reply_failed!(log, "Failed to save chunk data to store: {}", e);
+                let summary = if decoded_data.starts_with(b"PhoxJ") {
+                    "imported journal"
                 } else {
-                    reply!(log, "Data saved to file '{}'.", path);
+                    "imported world"
+                };
+                match saves.write(&decoded_data, &SaveMeta::without_world(path, 0, summary)) {
+                    Ok(()) => reply!(log, "Data saved to file '{}'.", path),
+                    Err(e) => reply_error(&mut log, "Failed to save imported data", e),
                 }
             }
         }
@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply};
+
+use crate::voxels::cellular_automata::History;
+
+/// Undo the last COUNT edits
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "undo")]
+pub struct UndoCommand {
+    #[arg(value_name = "COUNT", default_value = "1")]
+    count: usize,
+}
+
+/// Redo the last COUNT undone edits
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "redo")]
+pub struct RedoCommand {
+    #[arg(value_name = "COUNT", default_value = "1")]
+    count: usize,
+}
+
+pub fn undo_command(mut log: ConsoleCommand<UndoCommand>, mut history: ResMut<History>) {
+    if let Some(Ok(UndoCommand { count })) = log.take() {
+        let count = count.min(history.undo_steps());
+        for _ in 0..count {
+            history.undo();
+        }
+        reply!(log, "Undoing {} edits", count);
+    }
+}
+
+pub fn redo_command(mut log: ConsoleCommand<RedoCommand>, mut history: ResMut<History>) {
+    if let Some(Ok(RedoCommand { count })) = log.take() {
+        let count = count.min(history.redo_steps());
+        for _ in 0..count {
+            history.redo();
+        }
+        reply!(log, "Redoing {} edits", count);
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_console::reply;
+use bevy_console::{ConsoleCommand, clap::Parser, reply_failed};
+
+use super::reply_error;
+use crate::saves::{SaveMeta, Saves, read_or_migrate};
+use crate::voxels::ChunkManager;
+use crate::voxels::cellular_automata::{Journal, Replay, VoxelTick};
+
+/// Record every block edit so a session can be played back exactly
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "journal")]
+pub enum JournalCommand {
+    /// snapshot the world at the end of this tick and start recording edits
+    Start,
+    /// stop recording and save the journal
+    Stop {
+        #[arg(value_name = "FILE", default_value = "journal")]
+        file: String,
+    },
+    /// load the world a journal started from and play its edits back
+    Replay {
+        #[arg(value_name = "FILE", default_value = "journal")]
+        file: String,
+    },
+}
+
+pub fn journal_command(
+    mut log: ConsoleCommand<JournalCommand>,
+    mut journal: ResMut<Journal>,
+    manager: Res<ChunkManager>,
+    store: Res<bevy_pkv::PkvStore>,
+    mut saves: ResMut<Saves>,
+    tick: Res<VoxelTick>,
+    mut commands: Commands,
+) {
+    if let Some(Ok(c)) = log.take() {
+        match c {
+            JournalCommand::Start => {
+                if journal.is_recording() {
+                    reply_failed!(log, "Already recording a journal");
+                    return;
+                }
+                journal.start();
+                reply!(log, "Recording will start at the end of this tick");
+            }
+            JournalCommand::Stop { file } => {
+                if !journal.is_recording() {
+                    reply_failed!(log, "Not recording a journal");
+                    return;
+                }
+                let edits = journal.edits().len();
+                let data = match journal.stop() {
+                    Ok(d) => d,
+                    Err(e) => {
+                        reply_error(&mut log, "Failed to save journal", e);
+                        return;
+                    }
+                };
+                let meta =
+                    SaveMeta::without_world(&file, tick.get(), format!("journal, {edits} edits"));
+                if let Err(e) = saves.write(&data, &meta) {
+                    reply_error(&mut log, "Failed to save journal", e);
+                    return;
+                }
+                reply!(
+                    log,
+                    "Saved {} edits to '{}', `export saved {}` to share it",
+                    edits,
+                    file,
+                    file
+                );
+            }
+            JournalCommand::Replay { file } => {
+                let data = match read_or_migrate(&mut saves, &store, &file) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        reply_error(&mut log, "Failed to read journal", e);
+                        return;
+                    }
+                };
+                let (replay, start) = match Replay::from_journal(&data) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        reply_error(&mut log, "Failed to read journal", e);
+                        return;
+                    }
+                };
+                match manager.load_world(start, &mut commands) {
+                    Ok(report) if report.is_clean() => {}
+                    // edits on a damaged world won't play back the same
+                    Ok(report) => {
+                        reply_failed!(log, "Journal start is damaged, {}", report);
+                        return;
+                    }
+                    Err(e) => {
+                        reply_error(&mut log, "Failed to load journal start", e);
+                        return;
+                    }
+                }
+                commands.insert_resource(replay);
+                reply!(log, "Replaying '{}'", file);
+            }
+        }
+    }
+}
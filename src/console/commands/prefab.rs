@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply};
+
+use crate::voxels::voxel_chunk::prefab::{ChunkPrefab, PendingStamps};
+
+/// Stamp a `.phoxel` prefab from the assets folder into the world
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "prefab")]
+pub struct PrefabCommand {
+    /// relative to assets, e.g. prefabs/copper_tests.phoxel
+    #[arg(value_name = "PATH")]
+    path: String,
+    #[arg(value_name = "X", default_value = "0")]
+    x: i32,
+    #[arg(value_name = "Y", default_value = "0")]
+    y: i32,
+    #[arg(value_name = "Z", default_value = "0")]
+    z: i32,
+    /// quarter turns about y
+    #[arg(value_name = "TURNS", default_value = "0")]
+    turns: u8,
+}
+
+pub fn prefab_command(
+    mut log: ConsoleCommand<PrefabCommand>,
+    asset_server: Res<AssetServer>,
+    mut stamps: ResMut<PendingStamps>,
+) {
+    if let Some(Ok(PrefabCommand {
+        path,
+        x,
+        y,
+        z,
+        turns,
+    })) = log.take()
+    {
+        let prefab = asset_server.load::<ChunkPrefab>(&path);
+        stamps.push(prefab, IVec3::new(x, y, z), turns);
+        reply!(log, "Stamping {} at {} {} {}", path, x, y, z);
+    }
+}
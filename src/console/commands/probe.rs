@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use super::shapes::{MAX_VOLUME, cuboid, joules, kelvin, queue};
+use crate::voxels::ChunkManager;
+use crate::voxels::cellular_automata::{Cells, Change, FixedNum, PendingEdits};
+
+/// Print everything about one cell
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "probe")]
+pub struct ProbeCommand {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+/// Set the tempreture of every cell in a box, blocks stay as they are
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "heat")]
+pub struct HeatCommand {
+    x1: i32,
+    y1: i32,
+    z1: i32,
+    x2: i32,
+    y2: i32,
+    z2: i32,
+    #[arg(value_parser = kelvin)]
+    kelvin: FixedNum,
+}
+
+/// Set the energy of every cell in a box, tempreture and phase follow from it
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "energy")]
+pub struct EnergyCommand {
+    x1: i32,
+    y1: i32,
+    z1: i32,
+    x2: i32,
+    y2: i32,
+    z2: i32,
+    #[arg(value_parser = joules)]
+    joules: FixedNum,
+}
+
+pub fn probe_command(
+    mut log: ConsoleCommand<ProbeCommand>,
+    manager: Res<ChunkManager>,
+    chunks: Query<&Cells>,
+) {
+    if let Some(Ok(ProbeCommand { x, y, z })) = log.take() {
+        let Some(cell) = manager
+            .get_chunk_and_local_block(x, y, z)
+            .and_then(|(entity, local)| {
+                let cells = chunks.get(entity).ok()?;
+                Some(cells.get_cell(local.x, local.y, local.z))
+            })
+        else {
+            reply_failed!(log, "No chunk loaded at {} {} {}", x, y, z);
+            return;
+        };
+        let phase = if cell.is_gas() {
+            "gas"
+        } else if cell.is_liquid() {
+            "liquid"
+        } else {
+            "solid"
+        };
+        reply!(log, "{} at {} {} {} ({})", cell.block, x, y, z, phase);
+        reply!(
+            log,
+            "tempreture: {}K ({}C)",
+            cell.tempreture,
+            cell.tempreture - FixedNum::lit("273.15")
+        );
+        reply!(log, "energy: {}", cell.energy);
+        reply!(log, "density: {}", cell.density);
+        reply!(log, "presure: {}", cell.presure);
+        reply!(log, "charge: {}", cell.charge);
+        reply!(log, "flux: {}", cell.flux);
+        reply!(log, "flags: {:?}", cell.flags);
+    }
+}
+
+pub fn heat_command(
+    mut log: ConsoleCommand<HeatCommand>,
+    manager: Res<ChunkManager>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        let Some(shape) = cuboid(IVec3::new(c.x1, c.y1, c.z1), IVec3::new(c.x2, c.y2, c.z2)) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let change = Change::Tempreture(c.kelvin);
+        let count = queue(shape.map(|p| (p, change)), &manager, &mut edits);
+        reply!(log, "Heating {} cells to {}K", count, c.kelvin);
+    }
+}
+
+pub fn energy_command(
+    mut log: ConsoleCommand<EnergyCommand>,
+    manager: Res<ChunkManager>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        let Some(shape) = cuboid(IVec3::new(c.x1, c.y1, c.z1), IVec3::new(c.x2, c.y2, c.z2)) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let change = Change::Energy(c.joules);
+        let count = queue(shape.map(|p| (p, change)), &manager, &mut edits);
+        reply!(
+            log,
+            "Setting the energy of {} cells to {}J",
+            count,
+            c.joules
+        );
+    }
+}
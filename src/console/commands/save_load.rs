@@ -1,6 +1,10 @@
 use bevy::prelude::*;
-use bevy_console::{ConsoleCommand, clap::Parser, reply_failed};
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
 
+use super::reply_error;
+use crate::MeltdownError;
+use crate::menu::MapSize;
+use crate::saves::{SaveMeta, Saves, read_or_migrate};
 use crate::voxels::cellular_automata::Cells;
 use crate::voxels::{ChunkId, ChunkManager};
 
@@ -49,7 +53,10 @@ pub fn chunk_save_command(
     manager: Res<ChunkManager>,
     chunks: Query<&Cells>,
     mut store: ResMut<bevy_pkv::PkvStore>,
+    mut saves: ResMut<Saves>,
+    map_size: Res<MapSize>,
     tick: Res<crate::voxels::cellular_automata::VoxelTick>,
+    seed: Res<crate::voxels::cellular_automata::WorldSeed>,
 ) {
     if let Some(Ok(c)) = log.take() {
         match c {
@@ -63,7 +70,8 @@ pub fn chunk_save_command(
                 let data = match manager.save_chunk(chunk_id, &chunks) {
                     Ok(d) => d,
                     Err(e) => {
-                        reply_failed!(log, "Failed to save chunk: {}", e);
+                        let e = MeltdownError::from(e).in_chunk(chunk_id);
+                        reply_error(&mut log, "Failed to save chunk", e);
                         return;
                     }
                 };
@@ -74,15 +82,17 @@ pub fn chunk_save_command(
             SaveCommand::World { file } => {
                 let path = if file.is_empty() { "auto" } else { &file };
 
-                let data = match manager.save_world(&chunks, tick.get()) {
+                let data = match manager.save_world(&chunks, tick.get(), seed.get()) {
                     Ok(d) => d,
                     Err(e) => {
-                        reply_failed!(log, "Failed to save world: {}", e);
+                        reply_error(&mut log, "Failed to save world", e);
                         return;
                     }
                 };
-                if let Err(e) = store.set(path, &data) {
-                    reply_failed!(log, "Failed to save world data to store: {}", e);
+                let meta = SaveMeta::new(path, tick.get(), map_size.0, chunks.iter());
+                match saves.write(&data, &meta) {
+                    Ok(()) => reply!(log, "Saved {}", meta),
+                    Err(e) => reply_error(&mut log, "Failed to save world", e),
                 }
             }
         }
@@ -93,6 +103,7 @@ pub fn chunk_load_command(
     mut log: ConsoleCommand<LoadCommand>,
     manager: Res<ChunkManager>,
     store: Res<bevy_pkv::PkvStore>,
+    mut saves: ResMut<Saves>,
     mut commands: Commands,
 ) {
     if let Some(Ok(c)) = log.take() {
@@ -104,18 +115,24 @@ pub fn chunk_load_command(
                 };
                 let chunk_id = ChunkId::new(x, y, z);
                 if let Err(e) = manager.load_chunk(chunk_id, &data, &mut commands) {
-                    reply_failed!(log, "Failed to save chunk: {}", e);
+                    let e = MeltdownError::from(e).in_chunk(chunk_id);
+                    reply_error(&mut log, "Failed to load chunk", e);
                 }
             }
             LoadCommand::World { file } => {
                 let path = if file.is_empty() { "auto" } else { &file };
 
-                let Ok(data) = store.get::<Vec<u8>>(&path) else {
-                    reply_failed!(log, "No data found for file: {}", file);
-                    return;
+                let data = match read_or_migrate(&mut saves, &store, path) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        reply_error(&mut log, "Failed to load world", e);
+                        return;
+                    }
                 };
-                if let Err(e) = manager.load_world(&data, &mut commands) {
-                    reply_failed!(log, "Failed to save chunk: {}", e);
+                match manager.load_world(&data, &mut commands) {
+                    Ok(report) if report.is_clean() => reply!(log, "{}", report),
+                    Ok(report) => reply_failed!(log, "Save is damaged, {}", report),
+                    Err(e) => reply_error(&mut log, "Failed to load world", e),
                 }
             }
         }
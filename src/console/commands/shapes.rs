@@ -0,0 +1,292 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use crate::voxels::cellular_automata::{Cells, Change, FixedNum, PendingEdits};
+use crate::voxels::{BlockType, ChunkManager};
+
+/// a box bigger than this in one command is almost certainly a typo
+pub(super) const MAX_VOLUME: i64 = 500_000;
+
+/// Set every block in a box
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "fill")]
+pub struct FillCommand {
+    x1: i32,
+    y1: i32,
+    z1: i32,
+    x2: i32,
+    y2: i32,
+    z2: i32,
+    #[arg(value_parser = placeable_block)]
+    block: BlockType,
+    /// starting tempreture, room tempreture if not set
+    #[arg(long, value_name = "KELVIN", value_parser = kelvin)]
+    temp: Option<FixedNum>,
+}
+
+/// Swap one block for another in a box
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "replace")]
+pub struct ReplaceCommand {
+    x1: i32,
+    y1: i32,
+    z1: i32,
+    x2: i32,
+    y2: i32,
+    z2: i32,
+    #[arg(value_parser = placeable_block)]
+    from: BlockType,
+    #[arg(value_parser = placeable_block)]
+    to: BlockType,
+    #[arg(long, value_name = "KELVIN", value_parser = kelvin)]
+    temp: Option<FixedNum>,
+}
+
+/// Set every block within RADIUS of a point
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "sphere")]
+pub struct SphereCommand {
+    x: i32,
+    y: i32,
+    z: i32,
+    radius: u32,
+    #[arg(value_parser = placeable_block)]
+    block: BlockType,
+    #[arg(long, value_name = "KELVIN", value_parser = kelvin)]
+    temp: Option<FixedNum>,
+}
+
+/// Set an upright cylinder with its base centered on a point
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "cylinder")]
+pub struct CylinderCommand {
+    x: i32,
+    y: i32,
+    z: i32,
+    radius: u32,
+    height: u32,
+    #[arg(value_parser = placeable_block)]
+    block: BlockType,
+    #[arg(long, value_name = "KELVIN", value_parser = kelvin)]
+    temp: Option<FixedNum>,
+}
+
+/// Set just the walls, floor and roof of a box
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "hollow")]
+pub struct HollowCommand {
+    x1: i32,
+    y1: i32,
+    z1: i32,
+    x2: i32,
+    y2: i32,
+    z2: i32,
+    #[arg(value_parser = placeable_block)]
+    block: BlockType,
+    #[arg(long, value_name = "KELVIN", value_parser = kelvin)]
+    temp: Option<FixedNum>,
+}
+
+pub fn fill_command(
+    mut log: ConsoleCommand<FillCommand>,
+    manager: Res<ChunkManager>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        let Some(shape) = cuboid(IVec3::new(c.x1, c.y1, c.z1), IVec3::new(c.x2, c.y2, c.z2)) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let shape = shape.map(|p| (p, c.block));
+        let count = queue(at_k(shape, c.temp), &manager, &mut edits);
+        reply!(log, "Setting {} blocks", count);
+    }
+}
+
+pub fn replace_command(
+    mut log: ConsoleCommand<ReplaceCommand>,
+    manager: Res<ChunkManager>,
+    chunks: Query<&Cells>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        let Some(shape) = cuboid(IVec3::new(c.x1, c.y1, c.z1), IVec3::new(c.x2, c.y2, c.z2)) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let shape = shape
+            .filter(|p| {
+                manager
+                    .get_chunk_and_local_block(p.x, p.y, p.z)
+                    .and_then(|(entity, local)| chunks.get(entity).ok().map(|cells| (cells, local)))
+                    .is_some_and(|(cells, local)| {
+                        cells.get_cell(local.x, local.y, local.z).block == c.from
+                    })
+            })
+            .map(|p| (p, c.to));
+        let count = queue(at_k(shape, c.temp), &manager, &mut edits);
+        reply!(log, "Setting {} blocks", count);
+    }
+}
+
+pub fn sphere_command(
+    mut log: ConsoleCommand<SphereCommand>,
+    manager: Res<ChunkManager>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        let center = IVec3::new(c.x, c.y, c.z);
+        let r = c.radius as i32;
+        let Some(shape) = cuboid(center - r, center + r) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let shape = shape
+            .filter(|p| p.distance_squared(center) <= r * r)
+            .map(|p| (p, c.block));
+        let count = queue(at_k(shape, c.temp), &manager, &mut edits);
+        reply!(log, "Setting {} blocks", count);
+    }
+}
+
+pub fn cylinder_command(
+    mut log: ConsoleCommand<CylinderCommand>,
+    manager: Res<ChunkManager>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        if c.height == 0 {
+            reply_failed!(log, "Cylinder needs a height");
+            return;
+        }
+        let base = IVec3::new(c.x, c.y, c.z);
+        let r = c.radius as i32;
+        let top = IVec3::new(r, c.height as i32 - 1, r);
+        let Some(shape) = cuboid(base - IVec3::new(r, 0, r), base + top) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let shape = shape
+            .filter(|p| (p.xz() - base.xz()).length_squared() <= r * r)
+            .map(|p| (p, c.block));
+        let count = queue(at_k(shape, c.temp), &manager, &mut edits);
+        reply!(log, "Setting {} blocks", count);
+    }
+}
+
+pub fn hollow_command(
+    mut log: ConsoleCommand<HollowCommand>,
+    manager: Res<ChunkManager>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    if let Some(Ok(c)) = log.take() {
+        let a = IVec3::new(c.x1, c.y1, c.z1);
+        let b = IVec3::new(c.x2, c.y2, c.z2);
+        let (min, max) = (a.min(b), a.max(b));
+        let Some(shape) = cuboid(min, max) else {
+            reply_failed!(log, "That is more than {} blocks", MAX_VOLUME);
+            return;
+        };
+        let shape = shape
+            .filter(|p| p.cmpeq(min).any() || p.cmpeq(max).any())
+            .map(|p| (p, c.block));
+        let count = queue(at_k(shape, c.temp), &manager, &mut edits);
+        reply!(log, "Setting {} blocks", count);
+    }
+}
+
+/// Void is the edge of the world, nothing can put it there
+pub(super) fn placeable_block(s: &str) -> Result<BlockType, String> {
+    match s.parse::<BlockType>() {
+        Ok(BlockType::Void) => Err("void can't be placed".to_string()),
+        Ok(block) => Ok(block),
+        Err(_) => Err(format!("no block called {s}")),
+    }
+}
+
+/// A finite number above zero, NaN and infinity would panic turning into a `FixedNum`
+fn above_zero(s: &str, what: &str) -> Result<FixedNum, String> {
+    let n = s.parse::<f64>().map_err(|e| e.to_string())?;
+    if !n.is_finite() || n <= 0. {
+        return Err(format!("{what} must be above zero"));
+    }
+    FixedNum::checked_from_num(n).ok_or_else(|| format!("{what} {n} is too big"))
+}
+
+pub(super) fn kelvin(s: &str) -> Result<FixedNum, String> {
+    above_zero(s, "tempreture in K")
+}
+
+pub(super) fn joules(s: &str) -> Result<FixedNum, String> {
+    above_zero(s, "energy")
+}
+
+/// Every position in the box between `a` and `b` inclusive
+pub(super) fn cuboid(a: IVec3, b: IVec3) -> Option<impl Iterator<Item = IVec3>> {
+    let (min, max) = (a.min(b), a.max(b));
+    let size = (max.as_i64vec3() - min.as_i64vec3()) + 1;
+    if size.x * size.y * size.z > MAX_VOLUME {
+        return None;
+    }
+    Some((min.y..=max.y).flat_map(move |y| {
+        (min.z..=max.z).flat_map(move |z| (min.x..=max.x).map(move |x| IVec3::new(x, y, z)))
+    }))
+}
+
+/// Start every cell fresh as its block, at room tempreture if there's no `temp`
+fn at_k(
+    shape: impl Iterator<Item = (IVec3, BlockType)>,
+    temp: Option<FixedNum>,
+) -> impl Iterator<Item = (IVec3, Change)> {
+    let k = temp.unwrap_or(FixedNum::lit("293.15"));
+    shape.map(move |(position, block)| (position, Change::AtK(block, k)))
+}
+
+/// Edits go through `PendingEdits` so they only land once the tick is done
+/// and the world can be modified, anything outside the loaded world is dropped
+pub(super) fn queue(
+    changes: impl Iterator<Item = (IVec3, Change)>,
+    manager: &ChunkManager,
+    edits: &mut PendingEdits,
+) -> usize {
+    let mut count = 0;
+    edits.new_action();
+    for (position, change) in changes {
+        if manager
+            .get_chunk_and_local_block(position.x, position.y, position.z)
+            .is_some()
+        {
+            edits.push_change(position, change);
+            count += 1;
+        }
+    }
+    count
+}
+
+#[test]
+fn void_is_not_a_block_argument() {
+    assert_eq!(placeable_block("water"), Ok(BlockType::Water));
+    assert!(placeable_block("Void").is_err());
+    assert!(FillCommand::try_parse_from(["fill", "0", "0", "0", "1", "1", "1", "void"]).is_err());
+}
+
+#[test]
+fn kelvin_must_be_a_real_tempreture() {
+    assert_eq!(kelvin("300"), Ok(FixedNum::lit("300")));
+    for bad in ["0", "-5", "NaN", "inf", "1e30", "hot"] {
+        assert!(kelvin(bad).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn shapes_cover_the_right_cells() {
+    assert_eq!(cuboid(IVec3::ONE, IVec3::ZERO).unwrap().count(), 8);
+    assert!(cuboid(IVec3::ZERO, IVec3::splat(1000)).is_none());
+    let min = IVec3::ZERO;
+    let max = IVec3::splat(2);
+    let shell = cuboid(min, max)
+        .unwrap()
+        .filter(|p| p.cmpeq(min).any() || p.cmpeq(max).any())
+        .count();
+    assert_eq!(shell, 27 - 1);
+}
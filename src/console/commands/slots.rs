@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply};
+
+use super::reply_error;
+use crate::saves::Saves;
+
+/// Manage the world save slots
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "saves")]
+pub enum SavesCommand {
+    List,
+    Delete {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+    Rename {
+        #[arg(value_name = "FROM")]
+        from: String,
+        #[arg(value_name = "TO")]
+        to: String,
+    },
+}
+
+pub fn saves_command(mut log: ConsoleCommand<SavesCommand>, mut saves: ResMut<Saves>) {
+    if let Some(Ok(c)) = log.take() {
+        match c {
+            SavesCommand::List => match saves.list() {
+                Ok(list) if list.is_empty() => reply!(log, "No saves yet"),
+                Ok(list) => {
+                    for meta in list {
+                        reply!(log, "{}", meta);
+                    }
+                }
+                Err(e) => reply_error(&mut log, "Failed to list saves", e),
+            },
+            SavesCommand::Delete { name } => match saves.delete(&name) {
+                Ok(()) => reply!(log, "Deleted {}", name),
+                Err(e) => reply_error(&mut log, "Failed to delete save", e),
+            },
+            SavesCommand::Rename { from, to } => match saves.rename(&from, &to) {
+                Ok(()) => reply!(log, "Renamed {} to {}", from, to),
+                Err(e) => reply_error(&mut log, "Failed to rename save", e),
+            },
+        }
+    }
+}
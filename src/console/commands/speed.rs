@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use crate::voxels::cellular_automata::{MAX_SCALE, MIN_SCALE, SimSpeed, VoxelTick};
+
+/// Show or change how fast the simulation runs
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "speed")]
+pub enum SpeedCommand {
+    /// how fast it's running now
+    Show,
+    /// stop ticking
+    Pause,
+    /// carry on at the last speed
+    Resume,
+    /// run N ticks then pause
+    Step {
+        #[arg(default_value_t = 1)]
+        n: u64,
+    },
+    /// ticks per 100ms, 0.25 to 8
+    Set { scale: f64 },
+    /// tick as fast as the machine can
+    Max,
+}
+
+pub fn speed_command(
+    mut log: ConsoleCommand<SpeedCommand>,
+    mut speed: ResMut<SimSpeed>,
+    tick: Res<VoxelTick>,
+) {
+    let Some(Ok(c)) = log.take() else {
+        return;
+    };
+    match c {
+        SpeedCommand::Show => {}
+        SpeedCommand::Pause => speed.pause(),
+        SpeedCommand::Resume => speed.resume(),
+        SpeedCommand::Step { n } => speed.step(n),
+        SpeedCommand::Set { scale } => {
+            if !(MIN_SCALE..=MAX_SCALE).contains(&scale) {
+                reply_failed!(log, "SCALE must be between {} and {}", MIN_SCALE, MAX_SCALE);
+                return;
+            }
+            speed.set_scale(scale);
+        }
+        SpeedCommand::Max => speed.set_unlimited(),
+    }
+    reply!(log, "Running {} at tick {}", *speed, tick.get());
+}
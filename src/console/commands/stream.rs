@@ -0,0 +1,40 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use crate::voxels::{ChunkManager, StreamDistance};
+
+/// Show or set how far around the player chunks stay loaded
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "stream")]
+pub struct StreamCommand {
+    /// chunks within this many columns get loaded
+    load: Option<u32>,
+    /// chunks past this many columns get unloaded, defaults to 2 more than LOAD
+    unload: Option<u32>,
+}
+
+pub fn stream_command(
+    mut log: ConsoleCommand<StreamCommand>,
+    mut distance: ResMut<StreamDistance>,
+    manager: Res<ChunkManager>,
+) {
+    let Some(Ok(c)) = log.take() else {
+        return;
+    };
+    if let Some(load) = c.load {
+        let unload = c.unload.unwrap_or(load.saturating_add(2));
+        if unload < load {
+            reply_failed!(log, "UNLOAD must be at least LOAD");
+            return;
+        }
+        *distance = StreamDistance { load, unload };
+    }
+    reply!(
+        log,
+        "Loading within {} and unloading past {}: {} chunks loaded, {} unloaded",
+        distance.load,
+        distance.unload,
+        manager.len(),
+        manager.unloaded_len()
+    );
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use bevy_console::{ConsoleCommand, clap::Parser, reply, reply_failed};
+
+use super::reply_error;
+use crate::voxels::cellular_automata::{Cells, VoxelTick, WorldSeed};
+use crate::voxels::{ChunkId, ChunkManager};
+
+/// Write the world as text so designs can be read and diffed
+#[derive(Parser, ConsoleCommand, Debug)]
+#[command(name = "text")]
+pub enum TextCommand {
+    /// write every chunk, the tick and the seed to FILE
+    World {
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+    /// write one chunk to FILE
+    Chunk {
+        x: i32,
+        y: i32,
+        z: i32,
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+    /// load every chunk in FILE back where it was
+    Load {
+        #[arg(value_name = "FILE")]
+        file: String,
+    },
+}
+
+pub fn text_command(
+    mut log: ConsoleCommand<TextCommand>,
+    manager: Res<ChunkManager>,
+    chunks: Query<&Cells>,
+    tick: Res<VoxelTick>,
+    seed: Res<WorldSeed>,
+    mut commands: Commands,
+) {
+    let Some(Ok(c)) = log.take() else {
+        return;
+    };
+    match c {
+        TextCommand::World { file } => {
+            let text = match manager.save_text_world(&chunks, tick.get(), seed.get()) {
+                Ok(text) => text,
+                Err(e) => {
+                    reply_error(&mut log, "Failed to export world", e);
+                    return;
+                }
+            };
+            match write(&file, &text) {
+                Ok(()) => reply!(log, "Exported {} chunks to {}", manager.len(), file),
+                Err(e) => reply_failed!(log, "Failed to write {}: {}", file, e),
+            }
+        }
+        TextCommand::Chunk { x, y, z, file } => {
+            let text = match manager.save_text_chunk(ChunkId::new(x, y, z), &chunks) {
+                Ok(text) => text,
+                Err(e) => {
+                    reply_error(&mut log, "Failed to export chunk", e);
+                    return;
+                }
+            };
+            match write(&file, &text) {
+                Ok(()) => reply!(log, "Exported chunk to {}", file),
+                Err(e) => reply_failed!(log, "Failed to write {}: {}", file, e),
+            }
+        }
+        TextCommand::Load { file } => {
+            let text = match read(&file) {
+                Ok(text) => text,
+                Err(e) => {
+                    reply_failed!(log, "Failed to read {}: {}", file, e);
+                    return;
+                }
+            };
+            match manager.load_text(text, &mut commands) {
+                Ok(loaded) => reply!(log, "Loaded {} chunks from {}", loaded, file),
+                Err(e) => reply_error(&mut log, "Failed to load text", e),
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(file: &str, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = std::path::Path::new(file).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(file, text)?;
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(file: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(std::fs::read_to_string(file)?)
+}
+
+// no files to write to in a browser
+#[cfg(target_arch = "wasm32")]
+fn write(_file: &str, _text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Err("text exports can't be written on web".into())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(_file: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Err("text exports can't be read on web".into())
+}
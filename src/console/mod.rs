@@ -13,7 +13,27 @@ pub fn plugin(app: &mut App) {
     .add_console_command::<commands::SaveCommand, _>(commands::chunk_save_command)
     .add_console_command::<commands::LoadCommand, _>(commands::chunk_load_command)
     .add_console_command::<commands::Export, _>(commands::chunk_export_command)
-    .add_console_command::<commands::Import, _>(commands::chunk_import_command);
+    .add_console_command::<commands::Import, _>(commands::chunk_import_command)
+    .add_console_command::<commands::JournalCommand, _>(commands::journal_command)
+    .add_console_command::<commands::SavesCommand, _>(commands::saves_command)
+    .add_console_command::<commands::AutosaveCommand, _>(commands::autosave_command)
+    .add_console_command::<commands::RewindCommand, _>(commands::rewind_command)
+    .add_console_command::<commands::PrefabCommand, _>(commands::prefab_command)
+    .add_console_command::<commands::BlueprintCommand, _>(commands::blueprint_command)
+    .add_console_command::<commands::FillCommand, _>(commands::fill_command)
+    .add_console_command::<commands::ReplaceCommand, _>(commands::replace_command)
+    .add_console_command::<commands::SphereCommand, _>(commands::sphere_command)
+    .add_console_command::<commands::CylinderCommand, _>(commands::cylinder_command)
+    .add_console_command::<commands::HollowCommand, _>(commands::hollow_command)
+    .add_console_command::<commands::ProbeCommand, _>(commands::probe_command)
+    .add_console_command::<commands::HeatCommand, _>(commands::heat_command)
+    .add_console_command::<commands::EnergyCommand, _>(commands::energy_command)
+    .add_console_command::<commands::UndoCommand, _>(commands::undo_command)
+    .add_console_command::<commands::RedoCommand, _>(commands::redo_command)
+    .add_console_command::<commands::TextCommand, _>(commands::text_command)
+    .add_console_command::<commands::StreamCommand, _>(commands::stream_command)
+    .add_console_command::<commands::SpeedCommand, _>(commands::speed_command)
+    .add_console_command::<commands::BreakCommand, _>(commands::break_command);
 
     commands::init(app);
 }
@@ -89,6 +89,8 @@ fn on_open(In(content): In<Entity>, mut commands: Commands, state: Res<TabState>
     let buttons = [
         CellMode::OFF,
         CellMode::TEMPERATURE,
+        CellMode::PRESURE,
+        CellMode::CHARGE,
         CellMode::DUMMY,
         CellMode::PHASE,
     ];
@@ -772,7 +774,7 @@ fn update_diagnostics(
             }
             continue;
         }
-        buffer.set_data(extract_component(data, FixedNum::lit("1000.")));
+        buffer.set_data(extract_component(data, FixedNum::lit("1000."), state.mode));
     }
 }
 
@@ -816,7 +818,21 @@ const ONE: FixedNum = FixedNum::ONE;
 const U8: FixedNum = FixedNum::lit("255.0");
 const U16: FixedNum = FixedNum::lit("65535.0");
 
-fn extract_component(item: &Cells, max: FixedNum) -> AutomitaDiagnosticChunk {
+/// Scale the value shown by `mode` into the 0-255 range the shader reads
+fn diagnostic_value(cell: &CellData, mode: CellMode) -> FixedNum {
+    if mode.intersects(CellMode::TEMPERATURE) {
+        cell.temperature() * FixedNum::lit("0.05")
+    } else if mode.intersects(CellMode::PRESURE) {
+        cell.pressure() * FixedNum::lit("0.5")
+    } else if mode.intersects(CellMode::CHARGE) {
+        // centered so negative charge is below 127
+        cell.charge * FixedNum::lit("0.5") + FixedNum::lit("127.5")
+    } else {
+        cell.temperature() * FixedNum::lit("0.05")
+    }
+}
+
+fn extract_component(item: &Cells, max: FixedNum, mode: CellMode) -> AutomitaDiagnosticChunk {
     let mut chunk = AutomitaDiagnosticChunk {
         blocks: [Data::ZERO; CHUNK_VOL / 8],
     };
@@ -830,14 +846,14 @@ fn extract_component(item: &Cells, max: FixedNum) -> AutomitaDiagnosticChunk {
         let item6 = item.get_by_index(i + 6);
         let item7 = item.get_by_index(i + 7);
 
-        let mut tt0 = item0.temperature() * FixedNum::lit("0.05");
-        let mut tt1 = item1.temperature() * FixedNum::lit("0.05");
-        let mut tt2 = item2.temperature() * FixedNum::lit("0.05");
-        let mut tt3 = item3.temperature() * FixedNum::lit("0.05");
-        let mut tt4 = item4.temperature() * FixedNum::lit("0.05");
-        let mut tt5 = item5.temperature() * FixedNum::lit("0.05");
-        let mut tt6 = item6.temperature() * FixedNum::lit("0.05");
-        let mut tt7 = item7.temperature() * FixedNum::lit("0.05");
+        let mut tt0 = diagnostic_value(&item0, mode);
+        let mut tt1 = diagnostic_value(&item1, mode);
+        let mut tt2 = diagnostic_value(&item2, mode);
+        let mut tt3 = diagnostic_value(&item3, mode);
+        let mut tt4 = diagnostic_value(&item4, mode);
+        let mut tt5 = diagnostic_value(&item5, mode);
+        let mut tt6 = diagnostic_value(&item6, mode);
+        let mut tt7 = diagnostic_value(&item7, mode);
         tt0 = tt0.clamp(FixedNum::ZERO, U8);
         tt1 = tt1.clamp(FixedNum::ZERO, U8);
         tt2 = tt2.clamp(FixedNum::ZERO, U8);
@@ -897,7 +913,7 @@ fn dummy_diagnostics() -> AutomitaDiagnosticChunk {
         dummmy.energy = FixedNum::from_num(8 * x);
         chunk.set_cell(x, y, z, dummmy);
     }
-    extract_component(&chunk, U8)
+    extract_component(&chunk, U8, CellMode::TEMPERATURE)
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -0,0 +1,72 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    diagnostics::DiagnosticSettings,
+    voxels::cellular_automata::{BatchingPlan, SimSpeed},
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, reg_tab)
+        .add_systems(Update, update_text);
+}
+
+fn reg_tab(mut settings: ResMut<DiagnosticSettings>, mut commands: Commands) {
+    let on_open = commands.register_system(on_open);
+    let on_close = commands.register_system(on_close);
+
+    settings.register_tab("Batching", on_open, on_close);
+}
+
+#[derive(Component)]
+struct PlanText;
+
+fn on_open(In(content): In<Entity>, mut commands: Commands) {
+    commands
+        .entity(content)
+        .with_child((Text::new("No plan yet"), PlanText));
+}
+
+fn on_close(content: In<Entity>, mut commands: Commands) {
+    commands.entity(*content).despawn_related::<Children>();
+}
+
+/// Show what the batches were planned on next to what they took, so an overrun can be traced to a batch
+fn update_text(
+    mut text: Query<&mut Text, With<PlanText>>,
+    plan: Res<BatchingPlan>,
+    speed: Res<SimSpeed>,
+) {
+    let Ok(mut text) = text.single_mut() else {
+        return;
+    };
+    if !plan.is_changed() && !speed.is_changed() && !text.is_added() {
+        return;
+    }
+    let mut out = String::new();
+    let _ = writeln!(out, "Speed: {}", *speed);
+    let _ = writeln!(
+        out,
+        "Last tick: {:.01}ms of {:.01}ms target",
+        plan.last_tick, plan.target
+    );
+    let _ = writeln!(out, "Overruns: {}", plan.overruns);
+    let _ = writeln!(out, "Frame without stepping: {:.02}ms", plan.base_frame);
+    let _ = writeln!(
+        out,
+        "Stepping: {:.02}ms on {:.01} threads",
+        plan.tick_cost, plan.parallelism
+    );
+    let _ = writeln!(out, "Batches: {}", plan.planned.len());
+    for (i, (cost, chunks)) in plan.planned.iter().enumerate() {
+        let _ = write!(out, "{i}: {chunks} chunks, planned {cost:.02}ms");
+        match plan.measured.get(i) {
+            Some(took) => {
+                let _ = writeln!(out, ", took {took:.02}ms");
+            }
+            None => out.push('\n'),
+        }
+    }
+    text.0 = out;
+}
@@ -10,6 +10,7 @@ use bevy::{
 };
 #[cfg(not(target_arch = "wasm32"))]
 mod automata;
+mod batching;
 mod chunk;
 mod entity;
 mod fps;
@@ -19,7 +20,7 @@ pub mod shader;
 mod reporting;
 
 pub use chunk::ChunkCount;
-pub use reporting::MaxValue;
+pub use reporting::{MaxValue, Report};
 
 use crate::GameState;
 pub struct MeltdownDiagnosticsPlugin;
@@ -28,7 +29,7 @@ impl Plugin for MeltdownDiagnosticsPlugin {
     fn build(&self, app: &mut App) {
         // init our settings
         app.init_resource::<DiagnosticSettings>();
-        app.add_plugins((fps::plugin, entity::plugin, chunk::plugin))
+        app.add_plugins((fps::plugin, entity::plugin, chunk::plugin, batching::plugin))
             .add_systems(
                 Update,
                 (toggle_window, on_click_tap).run_if(in_state(GameState::Game)),
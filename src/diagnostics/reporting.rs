@@ -1,18 +1,29 @@
+use crate::voxels::cellular_automata::{BreakHit, CellData};
+
+/// What the stepping threads send back over the `MaxValue` channel
+pub enum Report {
+    /// hottest cell in a chunk
+    Max(CellData),
+    Break(BreakHit),
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod not_wasm {
     use bevy::ecs::world::{FromWorld, World};
     use fixed::traits::Fixed;
 
-    use crate::voxels::cellular_automata::{CellData, CellFlags, FixedNum};
+    use super::Report;
+    use crate::voxels::cellular_automata::{BreakHit, CellFlags, FixedNum};
 
     pub struct MaxValue {
         max_temp: FixedNum,
-        channel: std::sync::mpsc::Receiver<CellData>,
-        sender: std::sync::mpsc::Sender<CellData>,
+        hits: Vec<BreakHit>,
+        channel: std::sync::mpsc::Receiver<Report>,
+        sender: std::sync::mpsc::Sender<Report>,
     }
 
     impl MaxValue {
-        pub fn get_sender(&self) -> std::sync::mpsc::Sender<CellData> {
+        pub fn get_sender(&self) -> std::sync::mpsc::Sender<Report> {
             self.sender.clone()
         }
 
@@ -25,10 +36,18 @@ mod not_wasm {
         }
 
         pub fn run(&mut self) {
-            while let Ok(data) = self.channel.try_recv() {
-                self.max_temp = self.max_temp.max(data.temperature());
+            while let Ok(report) = self.channel.try_recv() {
+                match report {
+                    Report::Max(data) => self.max_temp = self.max_temp.max(data.temperature()),
+                    Report::Break(hit) => self.hits.push(hit),
+                }
             }
         }
+
+        /// Breakpoint hits received by `run` since this was last called
+        pub fn take_hits(&mut self) -> Vec<BreakHit> {
+            std::mem::take(&mut self.hits)
+        }
     }
 
     impl FromWorld for MaxValue {
@@ -36,6 +55,7 @@ mod not_wasm {
             let (sender, channel) = std::sync::mpsc::channel();
             MaxValue {
                 max_temp: FixedNum::ZERO,
+                hits: Vec::new(),
                 channel,
                 sender,
             }
@@ -45,13 +65,21 @@ mod not_wasm {
 
 #[cfg(target_arch = "wasm32")]
 mod wasm {
-    use crate::voxels::cellular_automata::CellData;
+    use std::sync::{Arc, Mutex};
+
+    use super::Report;
+    use crate::voxels::cellular_automata::{BreakHit, CellData};
+
+    /// no threads on web, so a shared list stands in for the channel
     #[derive(Default)]
-    pub struct MaxValue;
+    pub struct MaxValue {
+        reports: Arc<Mutex<Vec<Report>>>,
+        hits: Vec<BreakHit>,
+    }
 
     impl MaxValue {
         pub fn get_sender(&self) -> FakeSender {
-            FakeSender
+            FakeSender(self.reports.clone())
         }
 
         pub fn get_max(&self) -> CellData {
@@ -60,14 +88,32 @@ mod wasm {
 
         pub fn restart(&mut self) {}
 
-        pub fn run(&mut self) {}
+        pub fn run(&mut self) {
+            let mut reports = self.reports.lock().unwrap();
+            for report in reports.drain(..) {
+                if let Report::Break(hit) = report {
+                    self.hits.push(hit);
+                }
+            }
+        }
+
+        /// Breakpoint hits received by `run` since this was last called
+        pub fn take_hits(&mut self) -> Vec<BreakHit> {
+            std::mem::take(&mut self.hits)
+        }
     }
 
     #[derive(Clone)]
-    pub struct FakeSender;
+    pub struct FakeSender(Arc<Mutex<Vec<Report>>>);
 
     impl FakeSender {
-        pub fn send(&self, _data: CellData) {}
+        pub fn send(&self, data: Report) -> Result<(), ()> {
+            // max tempreture isn't shown on web, don't pile it up
+            if let Report::Break(_) = data {
+                self.0.lock().unwrap().push(data);
+            }
+            Ok(())
+        }
     }
 }
 
@@ -0,0 +1,151 @@
+use bevy::{ecs::error::BevyError, prelude::*};
+use chunk_serde::{BinError, StrError};
+
+use crate::{
+    headless::HeadlessError,
+    saves::SaveError,
+    voxels::{
+        ChunkId, ParseChunkIdError, blueprint::BlueprintError, chunk::ChunkManagerError,
+        prefab::PrefabError,
+    },
+};
+
+/// Everything that can go wrong, with the chunk and cell it went wrong in when we know them
+/// use `root` to match on what kind of failure it was
+#[derive(Debug, thiserror::Error)]
+pub enum MeltdownError {
+    #[error("{0}")]
+    Bin(BinError),
+    #[error("{0}")]
+    Str(StrError),
+    #[error("{0}")]
+    Chunk(ChunkManagerError),
+    #[error("{0}")]
+    ChunkId(#[from] ParseChunkIdError),
+    #[error("{0}")]
+    Save(#[from] SaveError),
+    #[error("{0}")]
+    Prefab(#[from] PrefabError),
+    #[error("{0}")]
+    Blueprint(#[from] BlueprintError),
+    #[error("{0}")]
+    Headless(#[from] HeadlessError),
+    #[error("{0}")]
+    Other(BevyError),
+    #[error("{chunk}: {source}")]
+    InChunk {
+        chunk: ChunkId,
+        source: Box<MeltdownError>,
+    },
+    #[error("cell {cell}: {source}")]
+    InCell {
+        cell: IVec3,
+        source: Box<MeltdownError>,
+    },
+}
+
+impl MeltdownError {
+    pub fn in_chunk(self, chunk: ChunkId) -> MeltdownError {
+        MeltdownError::InChunk {
+            chunk,
+            source: Box::new(self),
+        }
+    }
+
+    pub fn in_cell(self, cell: IVec3) -> MeltdownError {
+        MeltdownError::InCell {
+            cell,
+            source: Box::new(self),
+        }
+    }
+
+    /// The error with all the context taken off
+    pub fn root(&self) -> &MeltdownError {
+        match self {
+            MeltdownError::InChunk { source, .. } | MeltdownError::InCell { source, .. } => {
+                source.root()
+            }
+            error => error,
+        }
+    }
+
+    /// The chunk it happened in, if we know
+    pub fn chunk(&self) -> Option<ChunkId> {
+        match self {
+            MeltdownError::InChunk { chunk, .. } => Some(*chunk),
+            MeltdownError::InCell { source, .. } => source.chunk(),
+            MeltdownError::Chunk(ChunkManagerError::NoEntity(chunk)) => Some(*chunk),
+            _ => None,
+        }
+    }
+
+    /// The cell it happened in, if we know
+    pub fn cell(&self) -> Option<IVec3> {
+        match self {
+            MeltdownError::InCell { cell, .. } => Some(*cell),
+            MeltdownError::InChunk { source, .. } => source.cell(),
+            _ => None,
+        }
+    }
+
+    /// How far into the data it broke, if it was data that broke
+    pub fn offset(&self) -> Option<usize> {
+        match self.root() {
+            MeltdownError::Bin(error) => error.offset(),
+            MeltdownError::Str(error) => error.offset(),
+            _ => None,
+        }
+    }
+}
+
+impl From<BinError> for MeltdownError {
+    fn from(error: BinError) -> Self {
+        MeltdownError::Bin(error)
+    }
+}
+
+impl From<StrError> for MeltdownError {
+    fn from(error: StrError) -> Self {
+        MeltdownError::Str(error)
+    }
+}
+
+/// `chunk_serde` hands everything back as a `BevyError`, dig the real error back out
+impl From<BevyError> for MeltdownError {
+    fn from(error: BevyError) -> Self {
+        if let Some(error) = error.downcast_ref::<BinError>() {
+            MeltdownError::Bin(error.clone())
+        } else if let Some(error) = error.downcast_ref::<StrError>() {
+            MeltdownError::Str(error.clone())
+        } else if let Some(error) = error.downcast_ref::<ParseChunkIdError>() {
+            MeltdownError::ChunkId(error.clone())
+        } else {
+            MeltdownError::Other(error)
+        }
+    }
+}
+
+impl From<ChunkManagerError> for MeltdownError {
+    fn from(error: ChunkManagerError) -> Self {
+        match error {
+            ChunkManagerError::SerdeError(error) => error.into(),
+            error => MeltdownError::Chunk(error),
+        }
+    }
+}
+
+#[test]
+fn errors_keep_there_context() {
+    let error: BevyError = BinError::EOF.at(12).into();
+    let error = MeltdownError::from(ChunkManagerError::SerdeError(error))
+        .in_cell(IVec3::new(1, 2, 3))
+        .in_chunk(ChunkId::new(4, 5, 6));
+    assert_eq!(error.chunk(), Some(ChunkId::new(4, 5, 6)));
+    assert_eq!(error.cell(), Some(IVec3::new(1, 2, 3)));
+    assert_eq!(error.offset(), Some(12));
+    assert!(matches!(error.root(), MeltdownError::Bin(e) if matches!(e.root(), BinError::EOF)));
+    assert_eq!(
+        error.to_string(),
+        "Chunk(4,5,6): cell [1, 2, 3]: EOF at byte 12"
+    );
+}
@@ -0,0 +1,146 @@
+use std::path::Path;
+
+use bevy::{ecs::system::RunSystemOnce, prelude::*, state::app::StatesPlugin};
+
+use crate::{
+    GameState,
+    diagnostics::ChunkCount,
+    voxels::{
+        ChunkManager, LoadReport,
+        cellular_automata::{self, Cells, Replay, StateHashes, VoxelTick, WorldSeed},
+        chunk::ChunkManagerError,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HeadlessError {
+    #[error("Failed to read {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Failed to write {0}: {1}")]
+    Write(String, std::io::Error),
+    #[error("Failed to load world: {0}")]
+    Load(ChunkManagerError),
+    #[error("Save is damaged: {0}")]
+    Damaged(LoadReport),
+    #[error("Failed to save world: {0}")]
+    Save(ChunkManagerError),
+    #[error("Failed to run system: {0}")]
+    System(String),
+}
+
+/// Load a world saved with `ChunkManager::save_world`, a text export or a recorded journal,
+/// run `ticks` ticks as fast as possible and save the result to `output`.
+/// An `output` ending in `.phoxt` is written as a text export
+pub fn run_headless(input: &Path, output: &Path, ticks: u64) -> Result<(), HeadlessError> {
+    let mut app = load_app(input)?;
+    run_ticks(&mut app, ticks);
+
+    let as_text = output.extension().is_some_and(|ext| ext == "phoxt");
+    let data = app
+        .world_mut()
+        .run_system_once(
+            move |manager: Res<ChunkManager>,
+                  chunks: Query<&Cells>,
+                  tick: Res<VoxelTick>,
+                  seed: Res<WorldSeed>| {
+                if as_text {
+                    manager
+                        .save_text_world(&chunks, tick.get(), seed.get())
+                        .map(String::into_bytes)
+                } else {
+                    manager.save_world(&chunks, tick.get(), seed.get())
+                }
+            },
+        )
+        .map_err(|e| HeadlessError::System(e.to_string()))?
+        .map_err(HeadlessError::Save)?;
+    std::fs::write(output, data).map_err(|e| HeadlessError::Write(output.display().to_string(), e))
+}
+
+/// Load a world and run `ticks` ticks, returning the (tick, hash) of the world after each one
+pub fn record_hashes(input: &Path, ticks: u64) -> Result<Vec<(u64, u64)>, HeadlessError> {
+    let mut app = load_app(input)?;
+    app.init_resource::<StateHashes>();
+    run_ticks(&mut app, ticks);
+    Ok(app
+        .world_mut()
+        .remove_resource::<StateHashes>()
+        .unwrap_or_default()
+        .get()
+        .to_vec())
+}
+
+fn load_app(input: &Path) -> Result<App, HeadlessError> {
+    let data =
+        std::fs::read(input).map_err(|e| HeadlessError::Read(input.display().to_string(), e))?;
+
+    let mut app = headless_app();
+    if data.starts_with(b"PhoxT") {
+        let text = String::from_utf8(data).map_err(|e| {
+            let e = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+            HeadlessError::Read(input.display().to_string(), e)
+        })?;
+        app.world_mut()
+            .run_system_once(move |manager: Res<ChunkManager>, mut commands: Commands| {
+                manager.load_text(text.clone(), &mut commands)
+            })
+            .map_err(|e| HeadlessError::System(e.to_string()))?
+            .map_err(HeadlessError::Load)?;
+        return Ok(app);
+    }
+    // a journal is a world save with edits to play back on top
+    let (replay, data) = if data.starts_with(b"PhoxJ") {
+        let (replay, start) = Replay::from_journal(&data).map_err(HeadlessError::Load)?;
+        (Some(replay), start.to_vec())
+    } else {
+        (None, data)
+    };
+    let report = app
+        .world_mut()
+        .run_system_once(move |manager: Res<ChunkManager>, mut commands: Commands| {
+            manager.load_world(&data, &mut commands)
+        })
+        .map_err(|e| HeadlessError::System(e.to_string()))?
+        .map_err(HeadlessError::Load)?;
+    // a run from half a world isn't worth anything
+    if !report.is_clean() {
+        return Err(HeadlessError::Damaged(report));
+    }
+    if let Some(replay) = replay {
+        app.insert_resource(replay);
+    }
+
+    info!(
+        "loaded {} chunks at tick {} with seed {}",
+        app.world().resource::<ChunkManager>().len(),
+        app.world().resource::<VoxelTick>().get(),
+        app.world().resource::<WorldSeed>().get(),
+    );
+    Ok(app)
+}
+
+fn run_ticks(app: &mut App, ticks: u64) {
+    let timer = std::time::Instant::now();
+    for _ in 0..ticks {
+        app.update();
+    }
+    info!("ran {ticks} ticks in {:.2?}", timer.elapsed());
+}
+
+/// Just the automaton, no window, input or rendering
+fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        StatesPlugin,
+        bevy::log::LogPlugin::default(),
+    ));
+    app.insert_state(GameState::Game);
+    app.init_resource::<ChunkManager>()
+        .init_resource::<ChunkCount>();
+    app.add_plugins(cellular_automata::headless_plugin);
+    // we drive `update` by hand so never get to `App::run`
+    app.finish();
+    app.cleanup();
+    app
+}
@@ -232,6 +232,21 @@ fn setup_block_selector(mut commands: Commands) {
                                 TextColor(TEXT_COLOR),
                             ),]
                         ),
+                        // Control Rod
+                        (
+                            Button,
+                            button_node.clone(),
+                            BackgroundColor(get_block_color(BlockType::ControlRod)),
+                            BorderColor(NORMAL_BUTTON_BORDER),
+                            BlockButton {
+                                block_type: BlockType::ControlRod
+                            },
+                            children![(
+                                Text::new("Rod\n[9]"),
+                                button_text_font.clone(),
+                                TextColor(TEXT_COLOR),
+                            ),]
+                        ),
                     ]
                 ),
             ]
@@ -250,6 +265,8 @@ fn get_block_color(block_type: BlockType) -> Color {
         BlockType::Thorium => Color::srgb(0.6, 0.2, 0.8),
         BlockType::Wax => Color::srgb(0.9, 0.9, 0.6),
         BlockType::Rubber => Color::srgb(0.3, 0.3, 0.3),
+        BlockType::ControlRod => Color::srgb(0.15, 0.15, 0.15),
+        BlockType::SpentFuel => Color::srgb(0.3, 0.4, 0.2),
         BlockType::Void => Color::srgb(0.1, 0.0, 0.2),
     }
 }
@@ -14,14 +14,22 @@ use crate::voxels::map::ChunkData;
 
 pub mod voxels;
 
+pub use error::MeltdownError;
+pub use headless::{HeadlessError, record_hashes, run_headless};
+pub use saves::SaveError;
 pub use utils::BlockIter;
+pub use voxels::cellular_automata::first_divergence;
 
+mod autosave;
 mod console;
 mod diagnostics;
+mod error;
+mod headless;
 mod hotbar;
 mod menu;
 mod player;
 mod raycast;
+mod saves;
 mod ui;
 
 const TARGET_TICKTIME: f64 = 100.; // 10 ticks per second
@@ -78,12 +86,12 @@ pub fn run_game() {
             voxel_raycast_plugin,
             CrosshairPlugin,
         ));
-    app.add_plugins(block_selector_plugin);
+    app.add_plugins((block_selector_plugin, ui::speed::plugin));
     // #[cfg(debug_assertions)]
     // #[cfg(not(target_arch = "wasm32"))]
     // app.add_plugins(bevy_editor_pls::EditorPlugin::default());
 
-    app.add_plugins(console::plugin);
+    app.add_plugins((saves::plugin, autosave::plugin, console::plugin));
 
     // // dont know why some meshes are being detected as empty
     app.add_systems(Update, catch_failed_meshes);
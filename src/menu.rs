@@ -25,17 +25,37 @@ impl Default for MapSize {
     }
 }
 
+/// Seed for new worlds, random unless one is set so a run can be repeated
+#[derive(Resource, Debug, Default, PartialEq, Copy, Clone)]
+pub struct MapSeed(pub Option<u64>);
+
+impl std::fmt::Display for MapSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(seed) => write!(f, "{seed}"),
+            None => write!(f, "random"),
+        }
+    }
+}
+
 #[derive(Component)]
 struct CurrentMapSizeDisplay;
 
+#[derive(Component)]
+struct CurrentMapSeedDisplay;
+
 // Tag component for the input field
 #[derive(Component)]
 struct MapSizeInputField;
 
+#[derive(Component)]
+struct MapSeedInputField;
+
 pub fn menu_plugin(app: &mut App) {
     app.init_state::<MenuState>()
         .add_plugins(bevy_simple_text_input::TextInputPlugin)
         .init_resource::<MapSize>()
+        .init_resource::<MapSeed>()
         .add_systems(OnEnter(GameState::Menu), menu_setup)
         .add_systems(OnEnter(MenuState::Main), main_menu_setup)
         .add_systems(OnEnter(MenuState::Settings), settings_menu_setup)
@@ -51,6 +71,8 @@ pub fn menu_plugin(app: &mut App) {
                 button_system,
                 update_map_size_display,
                 set_map_size_button_action,
+                update_map_seed_display,
+                set_map_seed_button_action,
             )
                 .run_if(in_state(GameState::Menu)),
         );
@@ -83,6 +105,7 @@ enum MenuButtonAction {
     Play,
     Settings,
     SetMapSize,
+    SetMapSeed,
     BackToMainMenu,
     BackToSettings,
     Quit,
@@ -204,7 +227,7 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 }
 
-fn settings_menu_setup(mut commands: Commands, map_size: Res<MapSize>) {
+fn settings_menu_setup(mut commands: Commands, map_size: Res<MapSize>, map_seed: Res<MapSeed>) {
     let button_node = Node {
         width: Val::Percent(90.0),
         height: Val::Percent(30.0),
@@ -246,7 +269,7 @@ fn settings_menu_setup(mut commands: Commands, map_size: Res<MapSize>) {
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 width: Val::Percent(50.0),
-                height: Val::Percent(50.0),
+                height: Val::Percent(80.0),
                 ..default()
             },
             BackgroundColor(CRIMSON.into()),
@@ -263,7 +286,7 @@ fn settings_menu_setup(mut commands: Commands, map_size: Res<MapSize>) {
                     TextColor(TEXT_COLOR),
                 ),
                 (
-                    input_field_node,
+                    input_field_node.clone(),
                     BackgroundColor(NORMAL_BUTTON),
                     TextInput::default(),
                     TextInputPlaceholder {
@@ -271,6 +294,7 @@ fn settings_menu_setup(mut commands: Commands, map_size: Res<MapSize>) {
                         text_color: Some(Color::srgb(0.5, 0.5, 0.5).into()),
                         ..Default::default()
                     },
+                    MapSizeInputField,
                 ),
                 (
                     Button,
@@ -283,6 +307,34 @@ fn settings_menu_setup(mut commands: Commands, map_size: Res<MapSize>) {
                         TextColor(TEXT_COLOR),
                     ),]
                 ),
+                (
+                    Text::new(format!("Map Seed: {}", *map_seed)),
+                    text_style.clone(),
+                    TextColor(TEXT_COLOR),
+                    CurrentMapSeedDisplay,
+                ),
+                (
+                    input_field_node,
+                    BackgroundColor(NORMAL_BUTTON),
+                    TextInput::default(),
+                    TextInputPlaceholder {
+                        value: "random".to_string(),
+                        text_color: Some(Color::srgb(0.5, 0.5, 0.5).into()),
+                        ..Default::default()
+                    },
+                    MapSeedInputField,
+                ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::SetMapSeed,
+                    children![(
+                        Text::new("Set Seed"),
+                        button_text_style.clone(),
+                        TextColor(TEXT_COLOR),
+                    ),]
+                ),
                 (
                     Button,
                     button_node,
@@ -322,6 +374,9 @@ fn menu_action(
                 MenuButtonAction::SetMapSize => {
                     //set_map_size_button_action
                 }
+                MenuButtonAction::SetMapSeed => {
+                    //set_map_seed_button_action
+                }
             }
         }
     }
@@ -343,7 +398,7 @@ fn set_map_size_button_action(
         (Changed<Interaction>, With<Button>),
     >,
     mut map_size: ResMut<MapSize>,
-    mut text_input_query: Query<(&TextInput, &mut TextInputValue)>,
+    mut text_input_query: Query<(&TextInput, &mut TextInputValue), With<MapSizeInputField>>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Pressed
@@ -379,3 +434,46 @@ fn set_map_size_button_action(
         }
     }
 }
+
+fn update_map_seed_display(
+    map_seed: Res<MapSeed>,
+    mut query: Query<&mut Text, With<CurrentMapSeedDisplay>>,
+) {
+    if map_seed.is_changed() {
+        for mut text in &mut query {
+            text.0 = format!("Map Seed: {}", *map_seed);
+        }
+    }
+}
+
+fn set_map_seed_button_action(
+    interaction_query: Query<
+        (&Interaction, &MenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut map_seed: ResMut<MapSeed>,
+    mut text_input_query: Query<&mut TextInputValue, With<MapSeedInputField>>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed
+            && let MenuButtonAction::SetMapSeed = menu_button_action
+        {
+            let Ok(mut text_input_value) = text_input_query.single_mut() else {
+                error!("Could not find the map seed input.");
+                continue;
+            };
+            // an empty input goes back to a random seed for every new world
+            let input = text_input_value.0.trim();
+            if input.is_empty() {
+                map_seed.0 = None;
+            } else if let Ok(seed) = input.parse::<u64>() {
+                map_seed.0 = Some(seed);
+            } else {
+                warn!("Invalid input: the seed has to be a positive integer, or empty for random");
+                continue;
+            }
+            info!("Map seed set to: {}", *map_seed);
+            text_input_value.0.clear();
+        }
+    }
+}
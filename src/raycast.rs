@@ -7,7 +7,7 @@ use crate::{
     voxels::{
         CHUNK_SIZE, ChunkId, ChunkManager,
         block::BlockType,
-        cellular_automata::{CellData, Cells},
+        cellular_automata::{CellData, Cells, PendingEdits},
     },
 };
 
@@ -29,9 +29,10 @@ struct DebugUIContent;
 
 pub fn handle_voxel_interaction(
     camera_query: Query<&Transform, (With<Camera3d>, With<Player>)>,
-    mut chunks_query: Query<(&ChunkId, &mut Cells)>,
+    chunks_query: Query<(&ChunkId, &mut Cells)>,
     input: Res<ButtonInput<MouseButton>>,
     current_block: Res<CurrentBlock>,
+    mut edits: ResMut<PendingEdits>,
     mut debug_ui_visible: ResMut<DebugUIVisible>,
     mut last_click: Local<Option<MouseButton>>,
 ) {
@@ -50,18 +51,15 @@ pub fn handle_voxel_interaction(
         if let Some(solid_hit) =
             raycast_for_solid_block(start_pos, forward, max_distance, &chunks_query)
         {
-            println!(
+            debug!(
                 "Removing block at {:?}: {:?}",
                 solid_hit.voxel_position, solid_hit.cell_data
             );
 
-            if set_block_at_position(solid_hit.voxel_position, BlockType::Air, &mut chunks_query) {
-                println!("Successfully removed block");
-            } else {
-                println!("Failed to remove block - chunk not found");
-            }
+            edits.new_action();
+            edits.push(solid_hit.voxel_position, BlockType::Air);
         } else {
-            println!("No solid block found in range");
+            debug!("No solid block found in range");
         }
     }
 
@@ -76,24 +74,21 @@ pub fn handle_voxel_interaction(
                 // Choose block type based on key pressed
                 let block_type = current_block.0;
 
-                println!(
+                debug!(
                     "Placing {:?} block at {:?} (next to {:?})",
                     block_type, placement_pos, solid_hit.voxel_position
                 );
 
-                if set_block_at_position(placement_pos, block_type, &mut chunks_query) {
-                    println!("Successfully placed block");
-                } else {
-                    println!("Failed to place block - chunk not found");
-                }
+                edits.new_action();
+                edits.push(placement_pos, block_type);
             } else {
-                println!(
+                debug!(
                     "No suitable placement position found near {:?}",
                     solid_hit.voxel_position
                 );
             }
         } else {
-            println!("No solid block found to place against");
+            debug!("No solid block found to place against");
         }
     }
 
@@ -101,7 +96,7 @@ pub fn handle_voxel_interaction(
     if input.pressed(MouseButton::Middle) && *last_click != Some(MouseButton::Middle) {
         *last_click = Some(MouseButton::Middle);
         debug_ui_visible.0 = !debug_ui_visible.0;
-        println!("Debug UI toggled: {}", debug_ui_visible.0);
+        info!("Debug UI toggled: {}", debug_ui_visible.0);
     }
 
     if !input.pressed(MouseButton::Left)
@@ -187,6 +182,9 @@ fn update_debug_ui(
             content.push_str(&format!("Temperature: {:.1}K\n", hit.cell_data.tempreture));
             content.push_str(&format!("Energy: {:.2}kJ\n", hit.cell_data.energy));
             content.push_str(&format!("Density: {:.3}kg/m^3\n", hit.cell_data.density));
+            content.push_str(&format!("Pressure: {:.1}kPa\n", hit.cell_data.presure));
+            content.push_str(&format!("Charge: {:.2}C\n", hit.cell_data.charge));
+            content.push_str(&format!("Flux: {:.1}n\n", hit.cell_data.flux));
 
             if i < ray_hits.len() - 1 {
                 content.push_str("\n");
@@ -205,6 +203,9 @@ fn update_debug_ui(
                 content.push_str(&format!("Temperature: {:.1}K\n", hit.cell_data.tempreture));
                 content.push_str(&format!("Energy: {:.2}kJ\n", hit.cell_data.energy));
                 content.push_str(&format!("Density: {:.3}kg/m^3\n", hit.cell_data.density));
+                content.push_str(&format!("Pressure: {:.1}kPa\n", hit.cell_data.presure));
+                content.push_str(&format!("Charge: {:.2}C\n", hit.cell_data.charge));
+                content.push_str(&format!("Flux: {:.1}n\n", hit.cell_data.flux));
 
                 if i < ray_hits.len() - 1 {
                     content.push_str("\n");
@@ -320,51 +321,6 @@ fn get_block_at_position(
     }
 }
 
-fn set_block_at_position(
-    voxel_pos: IVec3,
-    block_type: BlockType,
-    chunks_query: &mut Query<(&ChunkId, &mut Cells)>,
-) -> bool {
-    let chunk_id = ChunkId(IVec3::from_array([
-        voxel_pos.x.div_euclid(CHUNK_SIZE),
-        voxel_pos.y.div_euclid(CHUNK_SIZE),
-        voxel_pos.z.div_euclid(CHUNK_SIZE),
-    ]));
-
-    let local_pos = IVec3::new(
-        voxel_pos.x.rem_euclid(CHUNK_SIZE),
-        voxel_pos.y.rem_euclid(CHUNK_SIZE),
-        voxel_pos.z.rem_euclid(CHUNK_SIZE),
-    );
-
-    for (id, mut cells) in chunks_query.iter_mut() {
-        if *id == chunk_id {
-            let CellData {
-                block: _,
-                energy,
-                tempreture,
-                density,
-                flags,
-            } = cells.get_cell(local_pos.x, local_pos.y, local_pos.z);
-            cells.set_cell(
-                local_pos.x,
-                local_pos.y,
-                local_pos.z,
-                CellData {
-                    block: block_type,
-                    energy,
-                    tempreture,
-                    density,
-                    flags,
-                },
-            );
-            return true;
-        }
-    }
-
-    false // Chunk not found
-}
-
 pub fn raycast_for_solid_block(
     start_pos: Vec3,
     direction: Vec3,
@@ -0,0 +1,456 @@
+use bevy::prelude::*;
+use strum::{EnumCount, IntoEnumIterator};
+
+use crate::voxels::{
+    CHUNK_VOL,
+    block::BlockType,
+    cellular_automata::{Cells, FixedNum},
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Saves>();
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SaveError {
+    #[error("No save called {0}")]
+    NotFound(String),
+    #[error("There is already a save called {0}")]
+    Exists(String),
+    #[error("{0:?} can't be used as a name")]
+    BadName(String),
+    #[error("Metadata for {0} is broken: {1}")]
+    BadMeta(String, String),
+    #[error("Failed to access saves: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to access store: {0}")]
+    Store(String),
+}
+
+/// Where save slots are kept, files on native and `PkvStore` on wasm
+pub trait SaveBackend: Send + Sync + 'static {
+    fn write(&mut self, data: &[u8], meta: &SaveMeta) -> Result<(), SaveError>;
+    fn read(&self, name: &str) -> Result<Vec<u8>, SaveError>;
+    /// every slot, newest first
+    fn list(&self) -> Result<Vec<SaveMeta>, SaveError>;
+    fn delete(&mut self, name: &str) -> Result<(), SaveError>;
+    fn rename(&mut self, from: &str, to: &str) -> Result<(), SaveError>;
+}
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct Saves(Box<dyn SaveBackend>);
+
+impl Default for Saves {
+    fn default() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Saves(Box::new(FileSaves::new("saves")));
+        #[cfg(target_arch = "wasm32")]
+        return Saves(Box::new(PkvSaves::new()));
+    }
+}
+
+/// What can be known about a save without loading it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveMeta {
+    pub name: String,
+    pub tick: u64,
+    /// in chunks
+    pub map_size: UVec3,
+    /// seconds since the unix epoch
+    pub timestamp: u64,
+    pub summary: String,
+}
+
+impl SaveMeta {
+    pub fn new<'a>(
+        name: &str,
+        tick: u64,
+        map_size: UVec3,
+        chunks: impl Iterator<Item = &'a Cells>,
+    ) -> SaveMeta {
+        SaveMeta {
+            name: name.to_string(),
+            tick,
+            map_size,
+            timestamp: now(),
+            summary: summarise(chunks),
+        }
+    }
+
+    /// For slots that aren't a snapshot of the running world, journals and imports
+    pub fn without_world(name: &str, tick: u64, summary: impl Into<String>) -> SaveMeta {
+        SaveMeta {
+            name: name.to_string(),
+            tick,
+            map_size: UVec3::ZERO,
+            timestamp: now(),
+            summary: summary.into(),
+        }
+    }
+
+    /// one `key=value` per line so it diffs nicely
+    fn to_text(&self) -> String {
+        format!(
+            "name={}\ntick={}\nmap_size={},{},{}\ntimestamp={}\nsummary={}\n",
+            self.name,
+            self.tick,
+            self.map_size.x,
+            self.map_size.y,
+            self.map_size.z,
+            self.timestamp,
+            self.summary
+        )
+    }
+
+    fn from_text(text: &str) -> Result<SaveMeta, String> {
+        let mut meta = SaveMeta {
+            name: String::new(),
+            tick: 0,
+            map_size: UVec3::ZERO,
+            timestamp: 0,
+            summary: String::new(),
+        };
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let number = |v: &str| v.trim().parse().map_err(|e| format!("{key}: {e}"));
+            match key {
+                "name" => meta.name = value.to_string(),
+                "tick" => meta.tick = number(value)?,
+                "timestamp" => meta.timestamp = number(value)?,
+                "summary" => meta.summary = value.to_string(),
+                "map_size" => {
+                    let size = value
+                        .split(',')
+                        .map(|v| v.trim().parse().map_err(|e| format!("{key}: {e}")))
+                        .collect::<Result<Vec<u32>, _>>()?;
+                    let [x, y, z] = size[..] else {
+                        return Err(format!("{key}: expected x,y,z"));
+                    };
+                    meta.map_size = UVec3::new(x, y, z);
+                }
+                _ => {} // from a newer version
+            }
+        }
+        if meta.name.is_empty() {
+            return Err("missing name".to_string());
+        }
+        Ok(meta)
+    }
+}
+
+impl std::fmt::Display for SaveMeta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: tick {}, map {}x{}x{}, saved at {}, {}",
+            self.name,
+            self.tick,
+            self.map_size.x,
+            self.map_size.y,
+            self.map_size.z,
+            self.timestamp,
+            self.summary
+        )
+    }
+}
+
+/// Read a slot, falling back to the `PkvStore` where everything went before there were slots.
+/// anything found there is copied into a slot so it shows up in the list from then on
+pub fn read_or_migrate(
+    saves: &mut Saves,
+    store: &bevy_pkv::PkvStore,
+    name: &str,
+) -> Result<Vec<u8>, SaveError> {
+    let not_found = match saves.read(name) {
+        Err(e @ SaveError::NotFound(_)) => e,
+        other => return other,
+    };
+    let Ok(data) = store.get::<Vec<u8>>(name) else {
+        return Err(not_found);
+    };
+    let meta = SaveMeta::without_world(name, 0, "moved from the old store");
+    match saves.write(&data, &meta) {
+        Ok(()) => info!("moved {name} from the old store into a save slot"),
+        Err(e) => warn!("Failed to move {name} into a save slot: {e}"),
+    }
+    Ok(data)
+}
+
+/// names end up as file names so keep them boring, blueprints use this too
+pub(crate) fn check_name(name: &str) -> Result<(), SaveError> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        return Err(SaveError::BadName(name.to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// SystemTime panics on wasm, ask the browser
+#[cfg(target_arch = "wasm32")]
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
+
+/// block counts and the hottest cell, instead of a thumbnail
+fn summarise<'a>(chunks: impl Iterator<Item = &'a Cells>) -> String {
+    let mut counts = [0u64; BlockType::COUNT];
+    let mut hottest = FixedNum::ZERO;
+    let mut total = 0;
+    for cells in chunks {
+        total += 1;
+        if cells.is_solid() {
+            let cell = cells.get_by_index(0);
+            counts[cell.block as usize] += CHUNK_VOL as u64;
+            hottest = hottest.max(cell.tempreture);
+            continue;
+        }
+        for cell in cells.blocks() {
+            counts[cell.block as usize] += 1;
+            hottest = hottest.max(cell.tempreture);
+        }
+    }
+    let blocks = BlockType::iter()
+        .filter(|b| !matches!(b, BlockType::Air | BlockType::Void) && counts[*b as usize] > 0)
+        .map(|b| format!("{} {}", b.as_ref(), counts[b as usize]))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{total} chunks, hottest {}K, {blocks}",
+        hottest.to_num::<i32>()
+    )
+}
+
+/// A folder with `<name>.phox` for the world and `<name>.meta` next to it
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileSaves {
+    dir: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSaves {
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> FileSaves {
+        FileSaves { dir: dir.into() }
+    }
+
+    fn data_path(&self, name: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{name}.phox"))
+    }
+
+    fn meta_path(&self, name: &str) -> std::path::PathBuf {
+        self.dir.join(format!("{name}.meta"))
+    }
+
+    fn not_found(name: &str) -> impl Fn(std::io::Error) -> SaveError {
+        move |e| match e.kind() {
+            std::io::ErrorKind::NotFound => SaveError::NotFound(name.to_string()),
+            _ => SaveError::Io(e),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SaveBackend for FileSaves {
+    fn write(&mut self, data: &[u8], meta: &SaveMeta) -> Result<(), SaveError> {
+        check_name(&meta.name)?;
+        std::fs::create_dir_all(&self.dir)?;
+        // write then move so a crash mid save doesn't eat the old one
+        let tmp = self.dir.join(format!("{}.tmp", meta.name));
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, self.data_path(&meta.name))?;
+        std::fs::write(self.meta_path(&meta.name), meta.to_text())?;
+        Ok(())
+    }
+
+    fn read(&self, name: &str) -> Result<Vec<u8>, SaveError> {
+        check_name(name)?;
+        std::fs::read(self.data_path(name)).map_err(Self::not_found(name))
+    }
+
+    fn list(&self) -> Result<Vec<SaveMeta>, SaveError> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut saves = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|e| e != "meta") {
+                continue;
+            }
+            let text = std::fs::read_to_string(&path)?;
+            match SaveMeta::from_text(&text) {
+                Ok(meta) => saves.push(meta),
+                Err(e) => warn!("skipping {}: {e}", path.display()),
+            }
+        }
+        saves.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(saves)
+    }
+
+    fn delete(&mut self, name: &str) -> Result<(), SaveError> {
+        check_name(name)?;
+        std::fs::remove_file(self.data_path(name)).map_err(Self::not_found(name))?;
+        let _ = std::fs::remove_file(self.meta_path(name)); // old saves might not have one
+        Ok(())
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> Result<(), SaveError> {
+        check_name(from)?;
+        check_name(to)?;
+        if self.data_path(to).exists() {
+            return Err(SaveError::Exists(to.to_string()));
+        }
+        std::fs::rename(self.data_path(from), self.data_path(to)).map_err(Self::not_found(from))?;
+        if let Ok(text) = std::fs::read_to_string(self.meta_path(from)) {
+            std::fs::remove_file(self.meta_path(from))?;
+            if let Ok(mut meta) = SaveMeta::from_text(&text) {
+                meta.name = to.to_string();
+                std::fs::write(self.meta_path(to), meta.to_text())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Browsers don't have a filesystem so slots go in local storage.
+/// keys get a prefix with a `/` in it, which `check_name` never lets through,
+/// so no save can land on the index or another slot's meta.
+/// saves from before slots are under the bare name and get moved by `read_or_migrate`
+#[cfg(target_arch = "wasm32")]
+pub struct PkvSaves {
+    store: bevy_pkv::PkvStore,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl PkvSaves {
+    const INDEX: &str = "save_slots";
+
+    fn data_key(name: &str) -> String {
+        format!("slot/{name}")
+    }
+
+    fn meta_key(name: &str) -> String {
+        format!("meta/{name}")
+    }
+
+    pub fn new() -> PkvSaves {
+        PkvSaves {
+            store: bevy_pkv::PkvStore::new("Phox", "meltdown_manager"),
+        }
+    }
+
+    fn index(&self) -> Vec<String> {
+        self.store
+            .get::<Vec<String>>(Self::INDEX)
+            .unwrap_or_default()
+    }
+
+    fn set_index(&mut self, index: Vec<String>) -> Result<(), SaveError> {
+        self.store
+            .set(Self::INDEX, &index)
+            .map_err(|e| SaveError::Store(e.to_string()))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SaveBackend for PkvSaves {
+    fn write(&mut self, data: &[u8], meta: &SaveMeta) -> Result<(), SaveError> {
+        check_name(&meta.name)?;
+        self.store
+            .set(Self::data_key(&meta.name), &data.to_vec())
+            .map_err(|e| SaveError::Store(e.to_string()))?;
+        self.store
+            .set(Self::meta_key(&meta.name), &meta.to_text())
+            .map_err(|e| SaveError::Store(e.to_string()))?;
+        let mut index = self.index();
+        if !index.contains(&meta.name) {
+            index.push(meta.name.clone());
+            self.set_index(index)?;
+        }
+        Ok(())
+    }
+
+    fn read(&self, name: &str) -> Result<Vec<u8>, SaveError> {
+        self.store
+            .get::<Vec<u8>>(Self::data_key(name))
+            .map_err(|_| SaveError::NotFound(name.to_string()))
+    }
+
+    fn list(&self) -> Result<Vec<SaveMeta>, SaveError> {
+        let mut saves = self
+            .index()
+            .iter()
+            .filter_map(|name| self.store.get::<String>(Self::meta_key(name)).ok())
+            .filter_map(|text| SaveMeta::from_text(&text).ok())
+            .collect::<Vec<_>>();
+        saves.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        Ok(saves)
+    }
+
+    fn delete(&mut self, name: &str) -> Result<(), SaveError> {
+        let mut index = self.index();
+        let Some(at) = index.iter().position(|n| n == name) else {
+            return Err(SaveError::NotFound(name.to_string()));
+        };
+        index.remove(at);
+        self.set_index(index)?;
+        let _ = self.store.remove(&Self::data_key(name));
+        let _ = self.store.remove(&Self::meta_key(name));
+        Ok(())
+    }
+
+    fn rename(&mut self, from: &str, to: &str) -> Result<(), SaveError> {
+        check_name(to)?;
+        if self.index().iter().any(|n| n == to) {
+            return Err(SaveError::Exists(to.to_string()));
+        }
+        let data = self.read(from)?;
+        let mut meta = self
+            .store
+            .get::<String>(Self::meta_key(from))
+            .map_err(|e| SaveError::BadMeta(from.to_string(), e.to_string()))
+            .and_then(|text| {
+                SaveMeta::from_text(&text).map_err(|e| SaveError::BadMeta(from.to_string(), e))
+            })?;
+        meta.name = to.to_string();
+        self.write(&data, &meta)?;
+        self.delete(from)
+    }
+}
+
+#[test]
+fn meta_round_trip() {
+    let meta = SaveMeta {
+        name: "reactor_2".to_string(),
+        tick: 1234,
+        map_size: UVec3::new(15, 3, 15),
+        timestamp: 1_700_000_000,
+        summary: "3 chunks, hottest 350K, Uranium 12".to_string(),
+    };
+    assert_eq!(SaveMeta::from_text(&meta.to_text()), Ok(meta));
+    assert!(SaveMeta::from_text("tick=3").is_err());
+}
+
+#[test]
+fn save_names_stay_in_the_folder() {
+    assert!(check_name("auto").is_ok());
+    assert!(check_name("reactor-2.old").is_ok());
+    for bad in ["", "../auto", "a/b", ".hidden", "a b"] {
+        assert!(check_name(bad).is_err(), "{bad}");
+    }
+}
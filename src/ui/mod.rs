@@ -1 +1,2 @@
 pub mod crosshair;
+pub mod speed;
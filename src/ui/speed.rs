@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+use bevy_console::ConsoleOpen;
+
+use crate::{
+    GameState,
+    voxels::cellular_automata::{MAX_SCALE, SimSpeed, Speed},
+};
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Game), setup_speed_panel)
+        .add_systems(
+            Update,
+            (
+                (speed_buttons, speed_keys),
+                button_colors,
+                update_speed_text,
+            )
+                .chain()
+                .run_if(in_state(GameState::Game)),
+        );
+}
+
+#[derive(Component)]
+struct SpeedText;
+
+/// What a button in the speed panel does, each has a key too for when the cursor is grabbed
+#[derive(Component, Clone, Copy)]
+enum SpeedButton {
+    Pause,
+    Step,
+    Slower,
+    Faster,
+    Max,
+}
+
+impl SpeedButton {
+    const ALL: [SpeedButton; 5] = [
+        SpeedButton::Pause,
+        SpeedButton::Step,
+        SpeedButton::Slower,
+        SpeedButton::Faster,
+        SpeedButton::Max,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SpeedButton::Pause => "||\n[P]",
+            SpeedButton::Step => ">|\n[.]",
+            SpeedButton::Slower => "-\n[[]",
+            SpeedButton::Faster => "+\n[]]",
+            SpeedButton::Max => ">>\n[\\]",
+        }
+    }
+
+    /// F10 was the old pause key so it still works
+    fn keys(&self) -> &'static [KeyCode] {
+        match self {
+            SpeedButton::Pause => &[KeyCode::KeyP, KeyCode::F10],
+            SpeedButton::Step => &[KeyCode::Period],
+            SpeedButton::Slower => &[KeyCode::BracketLeft],
+            SpeedButton::Faster => &[KeyCode::BracketRight],
+            SpeedButton::Max => &[KeyCode::Backslash],
+        }
+    }
+
+    fn apply(&self, speed: &mut SimSpeed) {
+        match self {
+            SpeedButton::Pause if speed.is_paused() => speed.resume(),
+            SpeedButton::Pause => speed.pause(),
+            SpeedButton::Step => speed.step(1),
+            // halving and doubling keeps to the speeds people expect, 0.25x up to 8x
+            SpeedButton::Slower => match speed.speed() {
+                Speed::Scale(scale) => speed.set_scale(scale / 2.),
+                Speed::Unlimited => speed.set_scale(MAX_SCALE),
+            },
+            SpeedButton::Faster => match speed.speed() {
+                Speed::Scale(scale) => speed.set_scale(scale * 2.),
+                Speed::Unlimited => {}
+            },
+            SpeedButton::Max => speed.set_unlimited(),
+        }
+    }
+}
+
+fn setup_speed_panel(mut commands: Commands) {
+    let button_font = TextFont {
+        font_size: 12.0,
+        ..default()
+    };
+    let panel = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.1, 0.1, 0.1, 0.8)),
+            children![(
+                Text::new("1x"),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(TEXT_COLOR),
+                SpeedText,
+            )],
+        ))
+        .id();
+    let row = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            ChildOf(panel),
+        ))
+        .id();
+    for button in SpeedButton::ALL {
+        commands.spawn((
+            Button,
+            Node {
+                width: Val::Px(36.0),
+                height: Val::Px(36.0),
+                margin: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(NORMAL_BUTTON),
+            button,
+            ChildOf(row),
+            children![(
+                Text::new(button.label()),
+                button_font.clone(),
+                TextColor(TEXT_COLOR),
+                TextLayout::new_with_justify(JustifyText::Center),
+            )],
+        ));
+    }
+}
+
+fn speed_buttons(
+    buttons: Query<(&Interaction, &SpeedButton), Changed<Interaction>>,
+    mut speed: ResMut<SimSpeed>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Pressed {
+            button.apply(&mut speed);
+        }
+    }
+}
+
+fn speed_keys(
+    input: Res<ButtonInput<KeyCode>>,
+    console: Res<ConsoleOpen>,
+    mut speed: ResMut<SimSpeed>,
+) {
+    // typing `speed set 0.5` shouldn't also slow it down
+    if console.open {
+        return;
+    }
+    for button in SpeedButton::ALL {
+        if input.any_just_pressed(button.keys().iter().copied()) {
+            button.apply(&mut speed);
+        }
+    }
+}
+
+fn button_colors(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<SpeedButton>),
+    >,
+) {
+    for (interaction, mut color) in &mut buttons {
+        *color = match interaction {
+            Interaction::Pressed => PRESSED_BUTTON.into(),
+            Interaction::Hovered => HOVERED_BUTTON.into(),
+            Interaction::None => NORMAL_BUTTON.into(),
+        };
+    }
+}
+
+fn update_speed_text(speed: Res<SimSpeed>, mut text: Query<&mut Text, With<SpeedText>>) {
+    if !speed.is_changed() {
+        return;
+    }
+    for mut text in &mut text {
+        text.0 = speed.to_string();
+    }
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 use chunk_serde::BinSerializer;
 use phoxels::core::BlockId;
+use strum::{IntoEnumIterator, VariantNames};
 
 #[derive(
     Clone,
@@ -13,7 +14,10 @@ use phoxels::core::BlockId;
     Default,
     strum_macros::EnumCount,
     strum_macros::AsRefStr,
+    strum_macros::VariantNames,
+    strum_macros::EnumString,
 )]
+#[strum(ascii_case_insensitive)]
 #[repr(u8)]
 pub enum BlockType {
     #[default]
@@ -26,6 +30,8 @@ pub enum BlockType {
     Thorium,
     Wax,
     Rubber,
+    ControlRod,
+    SpentFuel,
     Void,
 }
 
@@ -51,10 +57,25 @@ impl chunk_serde::Serialize for BlockType {
         Ok(1)
     }
     fn extract(slice: &[u8]) -> Result<(Self, usize)> {
-        #[cfg(debug_assertions)]
-        return Ok((BlockType::from_repr(slice[0]).unwrap(), 1));
-        #[cfg(not(debug_assertions))]
-        return Ok((BlockType::from_repr(slice[0]).unwrap_or(BlockType::Void), 1));
+        let Some(&id) = slice.first() else {
+            return Err(chunk_serde::BinError::EOF.into());
+        };
+        let block = BlockType::from_repr(id).ok_or(chunk_serde::BinError::InvalidId(id))?;
+        Ok((block, 1))
+    }
+    fn insert_str(&self, serializer: &mut chunk_serde::StrSerializer) -> Result<usize> {
+        let start = serializer.len();
+        serializer.push_str(self.as_ref());
+        Ok(serializer.len() - start)
+    }
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        let (name, used) = chunk_serde::take_word(str)?;
+        let block = BlockType::iter()
+            .find(|b| b.as_ref() == name)
+            .ok_or_else(|| {
+                chunk_serde::StrError::InValidName(name.to_string(), BlockType::VARIANTS)
+            })?;
+        Ok((block, used))
     }
 }
 
@@ -1,15 +1,19 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use bevy::{
     diagnostic::DiagnosticsStore,
     ecs::entity::{EntityHashSet, EntityIndexSet},
+    platform::time::Instant,
     prelude::*,
     scene::ron::de,
 };
 
 use crate::{
     GameState, TARGET_TICKTIME,
-    diagnostics::ChunkCount,
+    diagnostics::{ChunkCount, Report},
     utils::{BlockIter, CoreIter, EdgeIter},
     voxels::{
         ChunkId, Neighbours,
@@ -23,7 +27,6 @@ use crate::{
 pub enum BatchingStep {
     #[default]
     SetupWorld,
-    Pause,
     CalculateBatchs,
     Ready,
     Run,
@@ -151,6 +154,7 @@ impl VoxelStep {
 
 pub fn plugin(app: &mut App) {
     app.init_resource::<BatchingStrategy>()
+        .init_resource::<BatchingPlan>()
         .register_required_components::<Cells, NextStep>() // make sure Cells always has NextStep
         // If we are out of time for a tick, we force finish it in one frame --- makes Step = Done
         // If Step = Run, we run a batch of the simulation --- Sets Step = Done if all batches are finished
@@ -178,9 +182,11 @@ pub fn plugin(app: &mut App) {
         // If Step = Ready by time we get to last, set Step = Run --- this is the start of the tick
         // would be better if could be before update to avoid a wasted frame
         // ^ can't think of a way to do this that wouldn't clober Step before other systems get to do there checks
+        // sleeping chunks that were edited or sit next to a change need stepping again
         .add_systems(
             Last,
-            start_tick
+            (wake_chunks, start_tick)
+                .chain()
                 .run_if(in_step(BatchingStep::Ready))
                 .run_if(in_state(GameState::Game)),
         );
@@ -195,7 +201,12 @@ pub fn plugin(app: &mut App) {
     // If tick it finished, we update the state of the world --- makes Step = Ready
     app.configure_sets(
         Update,
-        (ApplyStep::PreApply, ApplyStep::Apply, ApplyStep::PostApply)
+        (
+            ApplyStep::PreApply,
+            ApplyStep::Apply,
+            ApplyStep::PostApply,
+            ApplyStep::Edit,
+        )
             .after(run_batch)
             .chain()
             .run_if(in_step(BatchingStep::Done))
@@ -217,32 +228,70 @@ pub fn plugin(app: &mut App) {
             .run_if(in_state(GameState::Game)),
     );
 
-    app.add_systems(Update, toggle_pause.run_if(in_state(GameState::Game)));
     app.add_systems(
         Update,
         update_meshs
-            .after(ApplyStep::PostApply)
+            .after(ApplyStep::Edit)
             .run_if(in_state(GameState::Game)),
     );
 
-    // this is what increments the target tick once per 1/10th of a second
+    // this is what increments the target tick once per 1/10th of a second, by however fast `SimSpeed` says
     // should run try run before we start a tick
     app.add_systems(
         FixedPostUpdate,
-        inc_target
-            .run_if(not(in_step(BatchingStep::Pause)))
-            .run_if(in_state(GameState::Game)),
+        inc_target.run_if(in_state(GameState::Game)),
     );
 
+    // finish the rest of the tick in one frame once it's taking longer than the speed allows
     #[cfg(feature = "sync")]
-    app.add_systems(FixedFirst, force_finish.run_if(in_step(BatchingStep::Run)))
-        .run_if(in_state(GameState::Game));
+    app.add_systems(
+        Update,
+        force_finish
+            .before(run_batch)
+            .run_if(in_step(BatchingStep::Run))
+            .run_if(tick_overdue)
+            .run_if(in_state(GameState::Game)),
+    );
+}
+
+#[cfg(feature = "sync")]
+fn tick_overdue(plan: Res<BatchingPlan>, speed: Res<SimSpeed>) -> bool {
+    plan.running_for() > speed.tick_time()
+}
+
+/// Run the simulation without a frame budget to share,
+/// every `Update` is one whole tick
+pub fn headless_plugin(app: &mut App) {
+    app.init_resource::<BatchingStrategy>()
+        .init_resource::<BatchingPlan>()
+        .register_required_components::<Cells, NextStep>();
+    app.configure_sets(
+        Update,
+        (
+            ApplyStep::PreApply,
+            ApplyStep::Apply,
+            ApplyStep::PostApply,
+            ApplyStep::Edit,
+        )
+            .after(step_all)
+            .chain(),
+    );
+    app.add_systems(Update, step_all);
+    app.add_systems(Update, set_prev.in_set(ApplyStep::Apply));
+    app.add_systems(Update, wake_chunks.after(ApplyStep::Edit));
+    app.add_systems(
+        Update,
+        apply_physics
+            .in_set(ApplyStep::PostApply)
+            .run_if(logic::is_step(StepMode::from_bits_retain(2))),
+    );
 }
 
 fn set_prev(
     mut chunks: Query<(Entity, &mut Cells, &mut NextStep)>,
     mut state: ResMut<VoxelStep>,
     mut batch: ResMut<NextBatch>,
+    mut plan: ResMut<BatchingPlan>,
     strategy: Res<BatchingStrategy>,
 ) {
     for (entity, mut chunk, mut next) in &mut chunks {
@@ -252,20 +301,29 @@ fn set_prev(
             continue;
         }
         next.has_run = false;
+        next.swapped = !next.is_asleep();
+        if next.is_asleep() {
+            continue; // wasn't stepped so the cells are already right
+        }
         std::mem::swap(chunk.bypass_change_detection(), &mut next.chunk);
+        next.unmeshed = true;
+        next.try_sleep();
     }
     batch.reset();
+    plan.finish_tick();
     state.set(BatchingStep::Ready);
 }
 
 fn update_batching(
     mut strategy: ResMut<BatchingStrategy>,
-    query: Query<Entity, With<Cells>>,
+    query: Query<(Entity, &NextStep), With<Cells>>,
     diagnostics: Res<DiagnosticsStore>,
     mut state: ResMut<VoxelStep>,
+    mut plan: ResMut<BatchingPlan>,
+    speed: Res<SimSpeed>,
     chunk_count: Res<crate::diagnostics::ChunkCount>,
 ) {
-    println!("calculating batching groups");
+    debug!("calculating batching groups");
 
     let frame_time = if let Some(b) =
         diagnostics.get(&bevy::diagnostic::FrameTimeDiagnosticsPlugin::FRAME_TIME)
@@ -275,35 +333,31 @@ fn update_batching(
         TARGET_TICKTIME
     };
 
-    // set min batch size to 10
-    let target = (chunk_count.get() / 10).max(5);
+    let costs = query
+        .iter()
+        .map(|(entity, next)| (entity, next.cost()))
+        .collect::<Vec<_>>();
+    let total_entitys = costs.len();
+    let groups = plan.plan(frame_time, speed.tick_time(), costs);
+    let batches = groups.len();
+    debug!(
+        "planned {} batches for {:.02}ms of stepping on a {:.02}ms frame",
+        batches, plan.tick_cost, plan.base_frame
+    );
 
-    // info!("Frame time: {:.04?}ms", frame_time);
-    let batches = ((TARGET_TICKTIME / frame_time) as usize).clamp(4, target) - 1;
-    // info!("Targeting {} batches for this tick", batches);
-    // info!(
-    //     "Targeting {} batches for this tick",
-    //     (TARGET_TICKTIME / frame_time)
-    // );
     strategy.reserve(batches);
     strategy.active_groups = batches;
-    let mut total_entitys = 0;
-
     strategy.clear();
-
-    for (i, entity) in query.iter().enumerate() {
-        total_entitys += 1;
-        strategy.groups[i % batches].insert(entity);
+    for (group, entities) in strategy.groups.iter_mut().zip(groups) {
+        group.extend(entities);
     }
     strategy.batch_size = total_entitys / batches;
-    // info!(
-    //     "set batches to {} with {} entities per batch",
-    //     batches, strategy.batch_size
-    // );
-    debug_assert_eq!(
-        chunk_count.get(),
+    // streamed in chunks are counted before they have cells to tick
+    debug_assert!(
+        total_entitys <= chunk_count.get(),
+        "Found more entities to tick than there are chunks {} > {}",
         total_entitys,
-        "Chunk count does not number of entities found to tick"
+        chunk_count.get()
     );
     state.set(BatchingStep::Ready);
 }
@@ -316,6 +370,7 @@ fn force_finish(
     mut next_state: ResMut<VoxelStep>,
     mut next_batch: ResMut<NextBatch>,
     tick: Res<VoxelTick>,
+    seed: Res<WorldSeed>,
 ) {
     for finish in strategy.batchs().skip(next_batch.get()) {
         next_batch.take();
@@ -324,6 +379,10 @@ fn force_finish(
                 let Ok(center_pre) = start_state.get(center) else {
                     return;
                 };
+                if chunk.is_asleep() {
+                    chunk.has_run = true;
+                    return;
+                }
                 let mut chunks = [Some(center_pre), None, None, None, None, None, None];
                 for (i, n) in neighbours.iter() {
                     if let Ok(neighbour) = start_state.get(n) {
@@ -336,6 +395,7 @@ fn force_finish(
                         ChunkIter::new(&mut chunk.chunk),
                         ChunkGared::new(chunks, *id),
                         tick.get(),
+                        seed.get(),
                     );
                 }
                 #[cfg(not(debug_assertions))]
@@ -344,9 +404,10 @@ fn force_finish(
                         ChunkIter::new(&mut chunk.chunk),
                         ChunkGared::new(chunks),
                         tick.get(),
+                        seed.get(),
                     );
                 }
-
+                chunk.settle(center_pre);
                 chunk.has_run = true;
             },
         );
@@ -355,6 +416,54 @@ fn force_finish(
     next_state.set(BatchingStep::Done);
 }
 
+/// step every chunk in one go
+fn step_all(
+    start_state: Query<&Cells>,
+    mut new_state: Query<(Entity, &ChunkId, &mut NextStep, &Neighbours), With<Cells>>,
+    mut state: ResMut<VoxelStep>,
+    mut tick: ResMut<VoxelTick>,
+    seed: Res<WorldSeed>,
+) {
+    tick.inc();
+    let tick = tick.get();
+    let seed = seed.get();
+    new_state
+        .par_iter_mut()
+        .for_each(|(center, id, mut chunk, neighbours)| {
+            let Ok(center_pre) = start_state.get(center) else {
+                warn!("Failed to get chunk {id:?} for stepping, skipping");
+                return;
+            };
+            if chunk.is_asleep() {
+                chunk.has_run = true;
+                return;
+            }
+            let mut chunks = [Some(center_pre), None, None, None, None, None, None];
+            for (i, n) in neighbours.iter() {
+                if let Ok(neighbour) = start_state.get(n) {
+                    chunks[i as usize + 1] = Some(neighbour);
+                }
+            }
+            #[cfg(debug_assertions)]
+            super::step(
+                ChunkIter::new(&mut chunk.chunk),
+                ChunkGared::new(chunks, *id),
+                tick,
+                seed,
+            );
+            #[cfg(not(debug_assertions))]
+            super::step(
+                ChunkIter::new(&mut chunk.chunk),
+                ChunkGared::new(chunks),
+                tick,
+                seed,
+            );
+            chunk.settle(center_pre);
+            chunk.has_run = true;
+        });
+    state.set(BatchingStep::Done);
+}
+
 fn run_batch(
     strategy: Res<BatchingStrategy>,
     mut state: ResMut<VoxelStep>,
@@ -362,7 +471,9 @@ fn run_batch(
     start_state: Query<&Cells>,
     mut new_state: Query<(Entity, &ChunkId, &mut NextStep, &Neighbours), With<Cells>>,
     mut next_batch: ResMut<NextBatch>,
+    mut plan: ResMut<BatchingPlan>,
     tick: Res<VoxelTick>,
+    seed: Res<WorldSeed>,
 ) {
     if strategy.is_empty() {
         error!("Batching strategy is empty, but we are in the run step. This is a bug.");
@@ -377,6 +488,9 @@ fn run_batch(
         error!("failed to get batch {current} from strategy, resetting batching");
         return;
     };
+    let started = Instant::now();
+    // nanoseconds spent stepping summed over every thread
+    let serial = AtomicU64::new(0);
     new_state.par_iter_many_unique_mut(batch).for_each_init(
         || sender.clone(),
         |max, (center, id, mut chunk, neighbours)| {
@@ -391,24 +505,40 @@ fn run_batch(
                 }
             }
             debug_assert!(!chunk.has_run);
+            if chunk.is_asleep() {
+                chunk.record_cost(0.);
+                chunk.has_run = true;
+                return;
+            }
+            let start = Instant::now();
             #[cfg(debug_assertions)]
             {
                 let out = super::logic::step_diag(
                     ChunkIter::new(&mut chunk.chunk),
                     ChunkGared::new(chunks, *id),
                     tick.get(),
+                    seed.get(),
                 );
-                let _ = max.send(out);
+                let _ = max.send(Report::Max(out));
             }
             #[cfg(not(debug_assertions))]
             super::step(
                 ChunkIter::new(&mut chunk.chunk),
                 ChunkGared::new(chunks),
                 tick.get(),
+                seed.get(),
             );
+            chunk.settle(center_pre);
+            let took = start.elapsed();
+            serial.fetch_add(took.as_nanos() as u64, Ordering::Relaxed);
+            chunk.record_cost(took.as_secs_f64() * 1000.);
             chunk.has_run = true;
         },
     );
+    plan.measure_batch(
+        started.elapsed().as_secs_f64() * 1000.,
+        serial.into_inner() as f64 / 1_000_000.,
+    );
 
     if next_batch.get() >= strategy.len() {
         state.set(BatchingStep::Done);
@@ -422,7 +552,7 @@ fn start_ticking(
     mut chunk_manager: ResMut<crate::voxels::ChunkManager>,
 ) {
     if generating_chunks.is_empty() {
-        println!(
+        info!(
             "all chunks({}) are generated, starting simulation",
             chunk_count.get()
         );
@@ -431,19 +561,30 @@ fn start_ticking(
     }
 }
 
-fn toggle_pause(
-    mut state: ResMut<VoxelStep>,
-    input: Res<ButtonInput<KeyCode>>,
-    mut local: Local<BatchingStep>,
+/// Wake anything that was edited or is next to a chunk that changed,
+/// this only ever wakes chunks so running it more than once a tick is fine
+fn wake_chunks(
+    mut chunks: Query<(&mut NextStep, &Neighbours)>,
+    edited: Query<Entity, Changed<Cells>>,
 ) {
-    if input.just_pressed(KeyCode::F10) {
-        if state.get() == BatchingStep::Pause {
-            info!("Resuming batching");
-            state.set(*local);
-        } else {
-            info!("Pausing batching");
-            *local = state.get();
-            state.set(BatchingStep::Pause);
+    let mut wake = Vec::new();
+    for (next, neighbours) in &chunks {
+        if next.is_active() {
+            wake.extend(neighbours.iter().map(|(_, neighbour)| neighbour));
+        }
+    }
+    for entity in &edited {
+        wake.push(entity);
+        if let Ok((_, neighbours)) = chunks.get(entity) {
+            wake.extend(neighbours.iter().map(|(_, neighbour)| neighbour));
+        }
+    }
+    for entity in wake {
+        let Ok((mut next, _)) = chunks.get_mut(entity) else {
+            continue;
+        };
+        if next.is_asleep() {
+            next.wake();
         }
     }
 }
@@ -453,24 +594,27 @@ fn batching_huristinc(
     mut state: ResMut<VoxelStep>,
     time: Res<Time<Real>>,
     mut last: Local<u32>,
-    diagnostics: Res<DiagnosticsStore>,
+    plan: Res<BatchingPlan>,
+    speed: Res<SimSpeed>,
     added: Query<(), Added<Cells>>,
+    mut removed: RemovedComponents<Cells>,
 ) {
-    if !added.is_empty() {
-        // if we added new chunks, we need to recalculate the batching groups
+    let removed = removed.read().count() > 0;
+    if !added.is_empty() || removed {
+        // if chunks were added or unloaded, we need to recalculate the batching groups
+        state.set(BatchingStep::CalculateBatchs);
+        return;
+    }
+    // the speed changed so the batches have a different amount of time to fit in
+    if plan.target != speed.tick_time() {
         state.set(BatchingStep::CalculateBatchs);
         return;
     }
 
+    // once a second replan if ticks are drifting off target
     if *last != time.elapsed_secs() as u32 {
         *last = time.elapsed_secs() as u32;
-        let Some(fps) = diagnostics
-            .get(&bevy::diagnostic::FrameTimeDiagnosticsPlugin::FPS)
-            .and_then(|v| v.average())
-        else {
-            return;
-        };
-        if fps < 30. {
+        if plan.is_off_target() {
             state.set(BatchingStep::CalculateBatchs);
         }
     }
@@ -481,7 +625,9 @@ fn start_tick(
     time: Res<Time<Real>>,
     mut local: Local<(u8, u32)>,
     mut tick: ResMut<VoxelTick>,
-    target: Res<TargetTick>,
+    mut plan: ResMut<BatchingPlan>,
+    mut target: ResMut<TargetTick>,
+    mut speed: ResMut<SimSpeed>,
 ) {
     debug_assert!(
         target.get() >= tick.get(),
@@ -489,7 +635,13 @@ fn start_tick(
         target.get(),
         tick.get()
     );
-    if tick.get() >= target.get() {
+    // paused only runs the steps asked for, unlimited never waits on the target
+    let ready = if speed.is_paused() {
+        speed.steps() > 0
+    } else {
+        speed.is_unlimited() || tick.get() < target.get()
+    };
+    if !ready {
         return;
     }
     if time.delta_secs_f64() > TARGET_TICKTIME {
@@ -499,6 +651,9 @@ fn start_tick(
         );
         return;
     }
+    if speed.is_paused() {
+        speed.take_step();
+    }
     local.0 += 1;
     if local.1 != time.elapsed_secs() as u32 {
         local.1 = time.elapsed_secs() as u32;
@@ -506,6 +661,11 @@ fn start_tick(
         local.0 = 0;
     }
     tick.inc();
+    // steps and unlimited ticks run past the target, keep it caught up so unpausing doesn't jump
+    if target.get() < tick.get() {
+        target.set(tick.get());
+    }
+    plan.start_tick();
     step.set(BatchingStep::Run);
 }
 
@@ -521,8 +681,9 @@ pub fn can_modify_world(s: Res<VoxelStep>) -> bool {
     s.0 == BatchingStep::Ready
 }
 
-pub fn can_fuck_with_next_step(s: Res<VoxelStep>) -> bool {
-    s.0 == BatchingStep::Pause
+/// paused between ticks, the cells can be poked at directly
+pub fn can_fuck_with_next_step(s: Res<VoxelStep>, speed: Res<SimSpeed>) -> bool {
+    speed.is_paused() && s.0 == BatchingStep::Ready
 }
 
 // struct NextStepRead<'w, 's> {
@@ -587,9 +748,11 @@ pub enum ApplyStep {
     PreApply,
     Apply,
     PostApply,
+    /// player and command edits, after physics so they land in the same place every run
+    Edit,
 }
 
-fn apply_physics(
+pub(super) fn apply_physics(
     mut chunks: Query<(Entity, Option<&mut Cells>), With<NextStep>>,
     neighbours: Query<(Entity, &ChunkId, &Neighbours)>,
     void_chunks: Res<VoidNeighbours>,
@@ -649,7 +812,7 @@ fn apply_physics(
                     continue; // target block is out of bounds
                 };
                 let mut other = other.flags;
-                other.remove(CellFlags::IS_GAS | CellFlags::IS_LIQUID);
+                other.remove(CellFlags::IS_GAS | CellFlags::IS_LIQUID | CellFlags::RUPTURED);
                 if other.bits() != direction.bits() {
                     continue; // target block is not trying to swap with this block
                 }
@@ -678,7 +841,7 @@ fn apply_physics(
                     continue; // target block is out of bounds
                 };
                 let mut other = other.flags;
-                other.remove(CellFlags::IS_GAS | CellFlags::IS_LIQUID);
+                other.remove(CellFlags::IS_GAS | CellFlags::IS_LIQUID | CellFlags::RUPTURED);
                 if other.bits() != direction.bits() {
                     continue; // target block is not trying to swap with this block
                 }
@@ -769,28 +932,89 @@ fn apply_physics(
     }
 }
 
+/// Only chunks whose block layout changed get remeshed, tempreture doesn't show on the mesh.
+/// Neighbours are remeshed too when a block on the face next to them changed
 fn update_meshs(
-    mut query: Query<(Entity, &Cells, &mut ChunkData), Changed<Cells>>,
+    mut query: Query<(
+        Entity,
+        Ref<Cells>,
+        &mut NextStep,
+        &mut ChunkData,
+        &Neighbours,
+    )>,
     mut mesher: ResMut<phoxels::ChunkMesher>,
 ) {
-    for (entity, cells, mut data) in &mut query {
-        if cells.is_solid() && cells.get_cell(0, 0, 0).get_block_type() == BlockType::Air {
-            continue; // skip chunks filled with air
+    let mut remesh = EntityHashSet::default();
+    for (entity, cells, mut next, mut data, neighbours) in &mut query {
+        if !cells.is_changed() && !next.unmeshed {
+            continue;
+        }
+        next.unmeshed = false;
+        let changed = data
+            .iter()
+            .zip(cells.blocks())
+            .enumerate()
+            .filter(|(_, (meshed, cell))| **meshed != cell.get_block_type())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        if changed.is_empty() {
+            continue;
         }
-        for (i, block) in cells.blocks().enumerate() {
+        // faces in `NeighbourDirection` order
+        let mut edges = [false; 6];
+        for i in changed {
+            let pos = Cells::position(i);
             data.set_block(
-                i as u32 % CHUNK_SIZE as u32,
-                i as u32 / (CHUNK_SIZE * CHUNK_SIZE) as u32,
-                (i as u32 / CHUNK_SIZE as u32) % CHUNK_SIZE as u32,
-                block.get_block_type(),
+                pos.x as u32,
+                pos.y as u32,
+                pos.z as u32,
+                cells.get_by_index(i).get_block_type(),
             );
+            edges[0] |= pos.y == CHUNK_SIZE - 1;
+            edges[1] |= pos.y == 0;
+            edges[2] |= pos.x == 0;
+            edges[3] |= pos.x == CHUNK_SIZE - 1;
+            edges[4] |= pos.z == CHUNK_SIZE - 1;
+            edges[5] |= pos.z == 0;
+        }
+        remesh.insert(entity);
+        for (direction, neighbour) in neighbours.iter() {
+            if edges[direction as usize] {
+                remesh.insert(neighbour);
+            }
+        }
+    }
+    for entity in remesh {
+        // neighbours that are still generating get meshed when they're done
+        if query.contains(entity) {
+            mesher.add_to_queue(entity);
         }
-        mesher.add_to_queue(entity);
     }
 }
 
-fn inc_target(mut target: ResMut<TargetTick>) {
-    target.inc();
-    target.inc();
-    target.inc();
+/// Add `scale * TICKS_PER_STEP` ticks to the target every fixed step, keeping the fraction for the next one
+fn inc_target(
+    mut target: ResMut<TargetTick>,
+    tick: Res<VoxelTick>,
+    speed: Res<SimSpeed>,
+    mut carry: Local<f64>,
+) {
+    let Some(scale) = speed.scale() else {
+        *carry = 0.;
+        return; // unlimited doesn't wait on the target
+    };
+    if speed.is_paused() {
+        *carry = 0.;
+        return;
+    }
+    *carry += scale * TICKS_PER_STEP;
+    while *carry >= 1. {
+        target.inc();
+        *carry -= 1.;
+    }
+    // don't build up more than a seconds worth of ticks to catch up on after a slow patch
+    let most = tick.get() + (1000. / TARGET_TICKTIME * scale * TICKS_PER_STEP).ceil() as u64;
+    if target.get() > most {
+        target.set(most);
+    }
 }
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use super::*;
+use crate::{
+    GameState,
+    diagnostics::{MaxValue, Report},
+    voxels::{ChunkId, block::BlockType},
+};
+
+const CHUNK_SIZE: i32 = crate::voxels::map::CHUNK_SIZE;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Breakpoints>();
+    // checked once physics has moved things so a hit sees the tick as it ends up
+    app.add_systems(
+        Update,
+        catch_breakpoints
+            .in_set(ApplyStep::PostApply)
+            .after(super::batching::apply_physics)
+            .run_if(in_state(GameState::Game)),
+    );
+}
+
+/// Something to stop the simulation on, checked against every chunk as it's stepped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+    /// a cell heating past this many K
+    Above(FixedNum),
+    /// a cell of this block turning solid, liquid or gas
+    Phase(BlockType),
+    /// anything about the cell at this world position changing
+    Cell(IVec3),
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Breakpoint::Above(k) => write!(f, "above {k}K"),
+            Breakpoint::Phase(block) => write!(f, "{block} changing phase"),
+            Breakpoint::Cell(pos) => write!(f, "cell {} {} {}", pos.x, pos.y, pos.z),
+        }
+    }
+}
+
+/// A breakpoint that went off, sent over the `MaxValue` channel
+#[derive(Debug, Clone, Copy)]
+pub struct BreakHit {
+    pub breakpoint: Breakpoint,
+    pub tick: u64,
+    /// position of the cell in the world
+    pub position: IVec3,
+    pub before: CellData,
+    pub after: CellData,
+}
+
+impl std::fmt::Display for BreakHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} on tick {} at {} {} {}: {} {}K -> {} {}K",
+            self.breakpoint,
+            self.tick,
+            self.position.x,
+            self.position.y,
+            self.position.z,
+            self.before.block,
+            self.before.tempreture,
+            self.after.block,
+            self.after.tempreture
+        )
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Breakpoints {
+    list: Vec<Breakpoint>,
+    /// what the simulation last stopped on
+    pub last_hits: Vec<BreakHit>,
+}
+
+impl Breakpoints {
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.list.iter()
+    }
+
+    pub fn add(&mut self, breakpoint: Breakpoint) {
+        self.list.push(breakpoint);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Breakpoint> {
+        if index >= self.list.len() {
+            return None;
+        }
+        Some(self.list.remove(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    /// Compare a chunk before and after it was stepped, calling `hit` once per breakpoint it set off
+    /// only the first cell is reported so a whole chunk heating up doesn't flood the channel
+    pub fn check(
+        &self,
+        chunk: ChunkId,
+        prev: &Cells,
+        next: &Cells,
+        tick: u64,
+        mut hit: impl FnMut(BreakHit),
+    ) {
+        for breakpoint in self.list.iter().copied() {
+            let found = match breakpoint {
+                Breakpoint::Cell(pos) => {
+                    let local = pos - chunk.0 * CHUNK_SIZE;
+                    if local.cmplt(IVec3::ZERO).any() || local.cmpge(IVec3::splat(CHUNK_SIZE)).any()
+                    {
+                        continue; // not in this chunk
+                    }
+                    let i = Cells::index(local.x, local.y, local.z);
+                    (prev.get_by_index(i) != next.get_by_index(i)).then_some(i)
+                }
+                Breakpoint::Above(k) => prev
+                    .blocks()
+                    .zip(next.blocks())
+                    .position(|(old, new)| old.temperature() <= k && new.temperature() > k),
+                Breakpoint::Phase(block) => {
+                    prev.blocks().zip(next.blocks()).position(|(old, new)| {
+                        old.get_block_type() == block
+                            && new.get_block_type() == block
+                            && (old.is_gas(), old.is_liquid()) != (new.is_gas(), new.is_liquid())
+                    })
+                }
+            };
+            let Some(i) = found else {
+                continue;
+            };
+            hit(BreakHit {
+                breakpoint,
+                tick,
+                position: chunk.0 * CHUNK_SIZE + Cells::position(i),
+                before: prev.get_by_index(i),
+                after: next.get_by_index(i),
+            });
+        }
+    }
+}
+
+/// Pause on the tick that set off a breakpoint, the world shows what it did
+fn catch_breakpoints(
+    chunks: Query<(&ChunkId, &Cells, &NextStep)>,
+    mut max: NonSendMut<MaxValue>,
+    mut breakpoints: ResMut<Breakpoints>,
+    mut speed: ResMut<SimSpeed>,
+    tick: Res<VoxelTick>,
+) {
+    if !breakpoints.is_empty() {
+        let sender = max.get_sender();
+        let list = &*breakpoints;
+        chunks.par_iter().for_each_init(
+            || sender.clone(),
+            |sender, (id, cells, next)| {
+                // only chunks stepped this tick still have the cells from before it
+                if !next.swapped {
+                    return;
+                }
+                list.check(*id, &next.chunk, cells, tick.get(), |hit| {
+                    let _ = sender.send(Report::Break(hit));
+                });
+            },
+        );
+    }
+    // drain even with nothing set so an old hit can't go off later
+    max.run();
+    let hits = max.take_hits();
+    if hits.is_empty() {
+        return;
+    }
+    for hit in &hits {
+        warn!("breakpoint hit: {hit}");
+    }
+    breakpoints.last_hits = hits;
+    speed.pause();
+}
+
+#[test]
+fn breakpoints_catch_the_changed_cell() {
+    let cold = CellData::at_k(BlockType::Water, FixedNum::lit("300"));
+    let prev = Cells::solid(cold);
+    let mut next = Cells::solid(cold);
+    next.set_not_solid();
+    next.set_by_index(
+        Cells::index(1, 2, 3),
+        CellData::at_k(BlockType::Water, FixedNum::lit("500")),
+    );
+
+    let mut breakpoints = Breakpoints::default();
+    breakpoints.add(Breakpoint::Above(FixedNum::lit("400")));
+    breakpoints.add(Breakpoint::Cell(IVec3::new(
+        CHUNK_SIZE + 1,
+        2,
+        CHUNK_SIZE + 3,
+    )));
+    breakpoints.add(Breakpoint::Cell(IVec3::new(0, 0, 0)));
+
+    let mut hits = Vec::new();
+    breakpoints.check(ChunkId::new(1, 0, 1), &prev, &next, 7, |hit| hits.push(hit));
+    assert_eq!(hits.len(), 2);
+    for hit in hits {
+        assert_eq!(hit.position, IVec3::new(CHUNK_SIZE + 1, 2, CHUNK_SIZE + 3));
+        assert_eq!(hit.tick, 7);
+    }
+}
@@ -14,6 +14,9 @@ pub struct CellData {
     pub energy: FixedNum,
     pub tempreture: FixedNum,
     pub density: FixedNum,
+    pub presure: FixedNum,
+    pub charge: FixedNum,
+    pub flux: FixedNum,
     pub flags: CellFlags,
 }
 
@@ -34,6 +37,9 @@ pub struct CellData {
 //     pub vaporization_energy: FixedNum,
 // }
 
+/// Only the block and energy, enough for the starting tempreture prefabs and blueprints want.
+/// charge comes back as `STD_CHARGE` and flux as zero,
+/// world saves write `SavedCell` instead which keeps both
 impl chunk_serde::Serialize for CellData {
     fn insert(&self, vec: &mut BinSerializer) -> Result<usize> {
         vec.push(self.block as u8);
@@ -44,58 +50,47 @@ impl chunk_serde::Serialize for CellData {
     }
 
     fn extract(slice: &[u8]) -> Result<(Self, usize)> {
+        if slice.len() < 5 {
+            return Err(chunk_serde::BinError::EOF.into());
+        }
         let mut out = CellData {
-            block: BlockType::from_repr(slice[0]).unwrap_or(BlockType::Void),
+            block: BlockType::from_repr(slice[0])
+                .ok_or(chunk_serde::BinError::InvalidId(slice[0]))?,
             energy: FixedNum::from_be_bytes(slice[1..5].try_into().unwrap()),
             tempreture: FixedNum::ONE, // Will be set later
             density: FixedNum::ONE,    // Will be set later
+            presure: ATM_1,            // Will be set later
+            charge: STD_CHARGE,
+            flux: FixedNum::ZERO,
             flags: CellFlags::empty(),
         };
         out.set_tempreture();
         out.set_phase();
         out.set_density();
+        out.set_presure(ATM_1);
         Ok((out, 5))
     }
 
-    // fn insert_str(&self, serializer: &mut chunk_serde::StrSerializer) -> Result<usize> {
-    //     let len = serializer.len();
-    //     serializer.write(format_args!(
-    //         "(temperature: {}, charge: {}, presure: {})",
-    //         self.temperature, self.charge, self.presure
-    //     ));
-    //     Ok(serializer.len() - len)
-    // }
-
-    // fn extract_str(str: &str) -> Result<(Self, usize)> {
-    //     let mut used = 0;
-    //     let mut skip = 0;
-    //     let mut temp = None;
-    //     let mut charge = None;
-    //     let mut presure = None;
-    //     for char in str.chars() {
-    //         used += 1;
-    //         if skip > 0 {
-    //             skip -= 1;
-    //             continue;
-    //         }
-    //         if char.is_whitespace() {
-    //             continue;
-    //         }
-    //         if char == '(' {
-    //             let word = str[used..]
-    //                 .split(':')
-    //                 .next()
-    //                 .ok_or(StrError::ExpectChar(':'))?;
-    //             used += word.len() + 1;
-    //             let (res, len) = FixedNum::e
-    //             match word {
-    //                 "temperature" => {
-    //                     used += 11;
-    //                 }
-    //             }
-    //         }
-    //     }
-    // }
+    /// only the block and tempreture, everything else follows from them
+    fn insert_str(&self, serializer: &mut chunk_serde::StrSerializer) -> Result<usize> {
+        let start = serializer.len();
+        serializer.write(format_args!("({}, {})", self.block, self.tempreture))?;
+        Ok(serializer.len() - start)
+    }
+
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        let mut used = chunk_serde::expect_char(str, '(')?;
+        let (block, len) = <BlockType as chunk_serde::Serialize>::extract_str(&str[used..])?;
+        used += len;
+        used += chunk_serde::expect_char(&str[used..], ',')?;
+        let (k, len) = chunk_serde::take_word(&str[used..])?;
+        let k = k.parse::<FixedNum>().map_err(|e| {
+            bevy::ecs::error::BevyError::from(format!("Invalid tempreture {k}: {e}"))
+        })?;
+        used += len;
+        used += chunk_serde::expect_char(&str[used..], ')')?;
+        Ok((CellData::at_k(block, k), used))
+    }
 }
 
 impl Default for CellData {
@@ -105,6 +100,9 @@ impl Default for CellData {
             energy: AIR_AT_20C.0,
             tempreture: FixedNum::lit("293.15"), // 20C in Kelvin
             density: FixedNum::lit("1.0"),       // Default density
+            presure: ATM_1,
+            charge: STD_CHARGE,
+            flux: FixedNum::ZERO,
             flags: AIR_AT_20C.1,
         }
     }
@@ -125,6 +123,9 @@ impl CellData {
             energy: at.0,
             density: block.properties().density.saturating_mul(d),
             tempreture: k,
+            presure: ATM_1,
+            charge: STD_CHARGE,
+            flux: FixedNum::ZERO,
             flags: at.1,
         }
     }
@@ -137,6 +138,7 @@ impl CellData {
         self.set_tempreture();
         self.set_phase();
         self.set_density();
+        self.set_presure(ATM_1);
     }
 
     pub fn get_block_type(&self) -> BlockType {
@@ -286,6 +288,10 @@ impl CellData {
         self.block.meta().conductivity(block as u8)
     }
 
+    pub const fn lookup_e(&self, block: BlockType) -> FixedNum {
+        self.block.meta().e_conductivity(block as u8)
+    }
+
     pub const fn properties(&self) -> &'static BlockProperties {
         self.block.properties()
     }
@@ -319,6 +325,8 @@ bitflags::bitflags! {
         const MOVE_BACK = 6 << 2;
         const MOVE_ALL = 7 << 2;
         const CAN_MOVE = 3;
+        /// broke apart this step, only lasts until the next step
+        const RUPTURED = 1 << 5;
     }
 }
 impl CellData {
@@ -336,6 +344,9 @@ impl CellData {
         flags: CellFlags::IS_GAS,
         density: FixedNum::ONE,
         tempreture: FixedNum::lit("271.15"),
+        presure: FixedNum::ZERO,
+        charge: STD_CHARGE,
+        flux: FixedNum::ZERO,
     };
 
     pub const MIN: CellData = CellData {
@@ -383,3 +394,22 @@ impl CellData {
         }
     }
 }
+
+impl CellData {
+    pub const fn pressure(&self) -> FixedNum {
+        self.presure
+    }
+
+    /// Gas presure follows its density and temperature,
+    /// liquids and solids don't compress so they just carry the `load` pushing on them
+    pub fn set_presure(&mut self, load: FixedNum) {
+        if self.is_gas() {
+            self.presure = self
+                .density()
+                .saturating_mul(self.temperature())
+                .saturating_mul(PRESURE_PER_K);
+        } else {
+            self.presure = load.max(ATM_1);
+        }
+    }
+}
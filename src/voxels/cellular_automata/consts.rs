@@ -3,6 +3,24 @@ pub type FixedNum = fixed::types::I25F7;
 pub const AIR_AT_20C: (FixedNum, CellFlags) = get_e_at_k(BlockType::Air, FixedNum::lit("293.15"));
 pub const ATM_1: FixedNum = FixedNum::lit("101.325");
 pub const STD_CHARGE: FixedNum = FixedNum::lit("0");
+/// kPa per Kelvin for a gas cell at density 1, picked so air at 20C sits at `ATM_1`
+pub const PRESURE_PER_K: FixedNum = FixedNum::lit("0.3456");
+/// Smallest presure difference (kPa) that will push a gas into its neighbour
+pub const PRESURE_FLOW_MIN: FixedNum = FixedNum::lit("1.0");
+/// kPa of stress a solid takes per Kelvin of difference to its neighbours
+pub const THERMAL_STRESS: FixedNum = FixedNum::lit("0.1");
+/// Fraction of the neutron flux that carries on each step, the rest escapes
+pub const NEUTRON_RETAIN: FixedNum = FixedNum::lit("0.5");
+/// Cap on flux in a single cell so a runaway can't overflow
+pub const FLUX_MAX: FixedNum = FixedNum::lit("100000");
+/// Fraction of `current * voltage` a cell turns into heat,
+/// the other half is picked up by the neighbour on its own step
+pub const JOULE_HEATING: FixedNum = FixedNum::lit("0.5");
+/// Biggest tempreture change (K) in a step that still counts as idle
+pub const SLEEP_DELTA: FixedNum = FixedNum::lit("0.05");
+/// Steps a chunk has to stay idle before it stops being stepped,
+/// long enough to go through every brownian direction
+pub const SLEEP_AFTER: u16 = 32;
 
 pub const fn get_e_at_k(block: BlockType, k: FixedNum) -> (FixedNum, CellFlags) {
     let props = block.properties();
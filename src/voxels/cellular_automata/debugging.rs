@@ -2,7 +2,7 @@ use super::*;
 pub fn plugin(app: &mut App) {
     app.add_systems(
         Update,
-        (melt_all, boil_all, set_all_300k)
+        (melt_all, boil_all, set_all_300k, charge_centers)
             .run_if(can_fuck_with_next_step)
             .run_if(is_cheat),
     );
@@ -49,6 +49,17 @@ fn set_all_300k(mut chunks: Query<&mut Cells>, input: Res<ButtonInput<KeyCode>>)
     }
 }
 
+fn charge_centers(mut chunks: Query<&mut Cells>, input: Res<ButtonInput<KeyCode>>) {
+    if !input.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+    for mut chunk in &mut chunks {
+        let mut cell = chunk.get_cell(5, 5, 5);
+        cell.charge = cell.charge.saturating_add(FixedNum::lit("100"));
+        chunk.set_cell(5, 5, 5, cell);
+    }
+}
+
 fn is_cheat(input: Res<ButtonInput<KeyCode>>) -> bool {
     input.pressed(KeyCode::Backquote)
 }
@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use super::*;
+use crate::voxels::ChunkId;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        hash_state
+            .after(ApplyStep::Edit)
+            .run_if(resource_exists::<StateHashes>),
+    );
+}
+
+/// Hash of every `Cells` after each tick
+/// only recorded while this resource exists
+#[derive(Resource, Default)]
+pub struct StateHashes(Vec<(u64, u64)>);
+
+impl StateHashes {
+    /// (tick, hash) in the order they ran
+    pub fn get(&self) -> &[(u64, u64)] {
+        &self.0
+    }
+
+    pub fn last_tick(&self) -> Option<u64> {
+        self.0.last().map(|(tick, _)| *tick)
+    }
+}
+
+/// First tick where `a` and `b` don't agree, or where one of them runs out
+pub fn first_divergence(a: &[(u64, u64)], b: &[(u64, u64)]) -> Option<u64> {
+    for (a, b) in a.iter().zip(b) {
+        if a != b {
+            return Some(a.0.min(b.0));
+        }
+    }
+    match a.len().cmp(&b.len()) {
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Less => Some(b[a.len()].0),
+        std::cmp::Ordering::Greater => Some(a[b.len()].0),
+    }
+}
+
+fn hash_state(
+    chunks: Query<(&ChunkId, &Cells)>,
+    tick: Res<VoxelTick>,
+    mut hashes: ResMut<StateHashes>,
+) {
+    if hashes.last_tick() == Some(tick.get()) {
+        return; // tick has not moved on since the last hash
+    }
+    let hash = hash_world(chunks.iter());
+    hashes.0.push((tick.get(), hash));
+}
+
+/// FNV-1a over every cell, chunks are sorted so the entity order doesn't matter
+pub fn hash_world<'a>(chunks: impl Iterator<Item = (&'a ChunkId, &'a Cells)>) -> u64 {
+    let mut chunks = chunks.collect::<Vec<_>>();
+    chunks.sort_by_key(|(id, _)| (id.x, id.y, id.z));
+    let mut hash = Fnv::default();
+    for (id, cells) in chunks {
+        hash.write(&id.x.to_le_bytes());
+        hash.write(&id.y.to_le_bytes());
+        hash.write(&id.z.to_le_bytes());
+        for cell in cells.blocks() {
+            hash.write(&[cell.block as u8, cell.flags.bits()]);
+            hash.write(&cell.energy.to_le_bytes());
+            hash.write(&cell.tempreture.to_le_bytes());
+            hash.write(&cell.density.to_le_bytes());
+            hash.write(&cell.presure.to_le_bytes());
+            hash.write(&cell.charge.to_le_bytes());
+            hash.write(&cell.flux.to_le_bytes());
+        }
+    }
+    hash.0
+}
+
+/// std's hasher is allowed to change between releases, this one can't
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[test]
+fn hash_changes_with_cells() {
+    let id = ChunkId::new(0, 0, 0);
+    let a = Cells::solid(CellData::default());
+    let mut b = a.clone();
+    assert_eq!(
+        hash_world([(&id, &a)].into_iter()),
+        hash_world([(&id, &b)].into_iter())
+    );
+    let mut cell = b.get_cell(1, 2, 3);
+    cell.energy += FixedNum::ONE;
+    b.set_cell(1, 2, 3, cell);
+    assert_ne!(
+        hash_world([(&id, &a)].into_iter()),
+        hash_world([(&id, &b)].into_iter())
+    );
+}
+
+#[test]
+fn divergence_is_first_mismatch() {
+    let a = [(1, 10), (2, 20), (3, 30)];
+    assert_eq!(first_divergence(&a, &a), None);
+    assert_eq!(first_divergence(&a, &[(1, 10), (2, 21), (3, 30)]), Some(2));
+    assert_eq!(first_divergence(&a, &a[..2]), Some(3));
+}
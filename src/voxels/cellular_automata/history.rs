@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_console::ConsoleOpen;
+
+use super::*;
+
+/// undo steps kept before the oldest is forgotten
+const MAX_ACTIONS: usize = 100;
+/// cells kept across every undo step, a big fill can eat the whole budget on its own
+const MAX_CELLS: usize = 200_000;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Update, undo_keys);
+}
+
+/// One cell an action changed, and what it was before and after
+#[derive(Debug, Clone, Copy)]
+pub struct CellEdit {
+    pub position: IVec3,
+    pub before: CellData,
+    pub after: CellData,
+}
+
+/// Edits grouped by action so they can be undone and redone,
+/// the undo and redo themselves happen with the rest of the edits between ticks
+#[derive(Resource, Default)]
+pub struct History {
+    pub(super) undo: VecDeque<Vec<CellEdit>>,
+    pub(super) redo: Vec<Vec<CellEdit>>,
+    pub(super) wants_undo: usize,
+    pub(super) wants_redo: usize,
+}
+
+impl History {
+    pub fn undo(&mut self) {
+        self.wants_undo += 1;
+    }
+
+    pub fn redo(&mut self) {
+        self.wants_redo += 1;
+    }
+
+    pub fn undo_steps(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_steps(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// a new action makes anything undone unreachable
+    pub(super) fn push(&mut self, action: Vec<CellEdit>) {
+        if action.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(action);
+        let mut cells = self.undo.iter().map(Vec::len).sum::<usize>();
+        while self.undo.len() > MAX_ACTIONS || (cells > MAX_CELLS && self.undo.len() > 1) {
+            if let Some(oldest) = self.undo.pop_front() {
+                cells -= oldest.len();
+            }
+        }
+    }
+}
+
+fn undo_keys(
+    mut history: ResMut<History>,
+    input: Res<ButtonInput<KeyCode>>,
+    console: Res<ConsoleOpen>,
+) {
+    // ctrl+z in the console is for the text, not the world
+    if console.open {
+        return;
+    }
+    if !input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    if input.just_pressed(KeyCode::KeyZ) {
+        history.undo();
+    }
+    if input.just_pressed(KeyCode::KeyY) {
+        history.redo();
+    }
+}
+
+#[test]
+fn history_is_bounded() {
+    let edit = CellEdit {
+        position: IVec3::ZERO,
+        before: CellData::default(),
+        after: CellData::default(),
+    };
+    let mut history = History::default();
+    history.redo.push(vec![edit]);
+    for _ in 0..MAX_ACTIONS + 5 {
+        history.push(vec![edit]);
+    }
+    assert_eq!(history.undo_steps(), MAX_ACTIONS);
+    assert_eq!(history.redo_steps(), 0);
+    history.push(vec![edit; MAX_CELLS]);
+    assert_eq!(history.undo_steps(), 1);
+}
@@ -0,0 +1,501 @@
+use bevy::prelude::*;
+use chunk_serde::{BinDeSerializer, BinSerializer, Palette};
+
+use super::history::{CellEdit, History};
+use super::*;
+use crate::voxels::{
+    ChunkId, ChunkManager, block::BlockType, block_palette, chunk::ChunkManagerError,
+    map::CHUNK_SIZE, palette_blocks,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PendingEdits>()
+        .init_resource::<Journal>()
+        .init_resource::<History>();
+    // edits only ever land between ticks so a replay puts them in the same place
+    app.add_systems(
+        Update,
+        (
+            replay_edits.run_if(resource_exists::<Replay>),
+            apply_edits,
+            start_recording,
+        )
+            .chain()
+            .in_set(ApplyStep::Edit),
+    );
+    // a paused world sits in Ready between ticks, edits still have to land
+    app.add_systems(
+        Update,
+        (
+            replay_edits.run_if(resource_exists::<Replay>),
+            apply_edits,
+            start_recording,
+        )
+            .chain()
+            .after(ApplyStep::Edit)
+            .run_if(can_modify_world),
+    );
+}
+
+/// Bumped whenever the way edits are written changes, old journals can't be replayed
+const JOURNAL_VERSION: u8 = 3;
+
+/// A block set by the player or a command, and the tick it went in after
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldEdit {
+    pub tick: u64,
+    pub position: IVec3,
+    pub change: Change,
+}
+
+/// What an edit does to the cell it lands on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// change the block but keep the cells energy
+    Block(BlockType),
+    /// start the cell fresh as the block at a tempreture
+    AtK(BlockType, FixedNum),
+    /// put back exactly this cell, what undo uses
+    Cell(CellData),
+    /// heat or cool whatever is there to a tempreture, the block stays
+    Tempreture(FixedNum),
+    /// set the energy of whatever is there, the tempreture follows from it
+    Energy(FixedNum),
+}
+
+impl Change {
+    pub fn apply(self, old: CellData) -> CellData {
+        match self {
+            Change::Block(block) => CellData { block, ..old },
+            Change::AtK(block, k) => CellData::at_k(block, k),
+            Change::Cell(cell) => cell,
+            Change::Tempreture(k) => {
+                let mut cell = CellData {
+                    energy: get_e_at_k(old.block, k).0,
+                    tempreture: k,
+                    ..old
+                };
+                cell.set_phase();
+                cell
+            }
+            Change::Energy(energy) => {
+                let mut cell = CellData { energy, ..old };
+                cell.set_tempreture();
+                cell.set_phase();
+                cell
+            }
+        }
+    }
+}
+
+/// Blocks waiting to be set, anything that wants to change the world should go through here
+/// edits are grouped into actions so they can be undone together
+#[derive(Resource, Default)]
+pub struct PendingEdits(Vec<Vec<(IVec3, Change)>>);
+
+impl PendingEdits {
+    /// everything pushed from here until the next call is one undo step
+    pub fn new_action(&mut self) {
+        if self.0.last().is_none_or(|action| !action.is_empty()) {
+            self.0.push(Vec::new());
+        }
+    }
+
+    /// change the block but keep the cells energy
+    pub fn push(&mut self, position: IVec3, block: BlockType) {
+        self.push_change(position, Change::Block(block));
+    }
+
+    /// replace the cell with `block` at `k` kelvin
+    pub fn push_at_k(&mut self, position: IVec3, block: BlockType, k: FixedNum) {
+        self.push_change(position, Change::AtK(block, k));
+    }
+
+    pub fn push_change(&mut self, position: IVec3, change: Change) {
+        if self.0.is_empty() {
+            self.0.push(Vec::new());
+        }
+        if let Some(action) = self.0.last_mut() {
+            action.push((position, change));
+        }
+    }
+}
+
+/// Every edit since recording started, along with the world as it was at the start
+#[derive(Resource, Default)]
+pub struct Journal {
+    /// take a snapshot at the end of the next tick and start recording
+    wants_start: bool,
+    recording: bool,
+    start: Vec<u8>,
+    edits: Vec<WorldEdit>,
+}
+
+impl Journal {
+    /// recording starts once the current tick has finished
+    pub fn start(&mut self) {
+        self.wants_start = true;
+    }
+
+    /// stop recording and get the journal to save
+    pub fn stop(&mut self) -> Result<Vec<u8>, ChunkManagerError> {
+        self.recording = false;
+        self.wants_start = false;
+        let mut serde = chunk_serde::BinSerializer::new();
+        serde
+            .insert(b"PhoxJ")
+            .map_err(ChunkManagerError::SerdeError)?;
+        serde.push(JOURNAL_VERSION);
+        // blocks in edits are ids into this so a reordered `BlockType` still replays
+        serde
+            .insert(&block_palette())
+            .map_err(ChunkManagerError::SerdeError)?;
+        serde
+            .insert(&(self.edits.len() as u64))
+            .map_err(ChunkManagerError::SerdeError)?;
+        for edit in &self.edits {
+            serde
+                .insert(&edit.tick)
+                .map_err(ChunkManagerError::SerdeError)?;
+            serde
+                .insert(&ChunkId(edit.position))
+                .map_err(ChunkManagerError::SerdeError)?;
+            insert_change(&mut serde, &edit.change).map_err(ChunkManagerError::SerdeError)?;
+        }
+        // the start is a normal world save so it goes last
+        let mut out = serde.finalize();
+        out.extend_from_slice(&std::mem::take(&mut self.start));
+        self.edits.clear();
+        Ok(out)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording || self.wants_start
+    }
+
+    pub fn edits(&self) -> &[WorldEdit] {
+        &self.edits
+    }
+}
+
+/// Edits from a journal waiting for there tick to come round
+#[derive(Resource)]
+pub struct Replay {
+    edits: Vec<WorldEdit>,
+    next: usize,
+}
+
+impl Replay {
+    /// Split a journal into the world it starts from and the edits to play back on it
+    pub fn from_journal(data: &[u8]) -> Result<(Replay, &[u8]), ChunkManagerError> {
+        let mut serde = BinDeSerializer::new(data);
+        let magic = serde
+            .extract::<[u8; 5]>()
+            .map_err(ChunkManagerError::SerdeError)?;
+        if magic != *b"PhoxJ" {
+            return Err(ChunkManagerError::SerdeError(
+                bevy::ecs::error::BevyError::from("Attempted to load journal from other data"),
+            ));
+        }
+        let [version] = serde
+            .extract::<[u8; 1]>()
+            .map_err(ChunkManagerError::SerdeError)?;
+        if version != JOURNAL_VERSION {
+            return Err(ChunkManagerError::SerdeError(
+                bevy::ecs::error::BevyError::from(format!(
+                    "Journal is version {version}, only version {JOURNAL_VERSION} can be replayed"
+                )),
+            ));
+        }
+        let blocks = serde
+            .extract::<Palette>()
+            .and_then(|palette| palette_blocks(&palette))
+            .map_err(ChunkManagerError::SerdeError)?;
+        let len = serde
+            .extract::<u64>()
+            .map_err(ChunkManagerError::SerdeError)?;
+        // every edit takes more than a byte so a bad length can't ask for more than the file
+        let mut edits = Vec::with_capacity(len.min(serde.remaining() as u64) as usize);
+        for _ in 0..len {
+            let tick = serde
+                .extract::<u64>()
+                .map_err(ChunkManagerError::SerdeError)?;
+            let position = serde
+                .extract::<ChunkId>()
+                .map_err(ChunkManagerError::SerdeError)?
+                .0;
+            let change =
+                extract_change(&mut serde, &blocks).map_err(ChunkManagerError::SerdeError)?;
+            edits.push(WorldEdit {
+                tick,
+                position,
+                change,
+            });
+        }
+        let start = &data[data.len() - serde.remaining()..];
+        Ok((Replay { edits, next: 0 }, start))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.edits.len()
+    }
+}
+
+fn replay_edits(
+    mut commands: Commands,
+    mut replay: ResMut<Replay>,
+    mut pending: ResMut<PendingEdits>,
+    tick: Res<VoxelTick>,
+) {
+    while let Some(edit) = replay.edits.get(replay.next) {
+        if edit.tick > tick.get() {
+            return;
+        }
+        pending.push_change(edit.position, edit.change);
+        replay.next += 1;
+    }
+    info!("replay finished at tick {}", tick.get());
+    commands.remove_resource::<Replay>();
+}
+
+fn apply_edits(
+    mut pending: ResMut<PendingEdits>,
+    mut journal: ResMut<Journal>,
+    mut history: ResMut<History>,
+    manager: Res<ChunkManager>,
+    mut chunks: Query<&mut Cells>,
+    tick: Res<VoxelTick>,
+) {
+    let mut apply = |position: IVec3, change: Change| {
+        let chunk = ChunkId(position.div_euclid(IVec3::splat(CHUNK_SIZE)));
+        let local = position.rem_euclid(IVec3::splat(CHUNK_SIZE));
+        let Some(mut cells) = manager
+            .get_chunk(&chunk)
+            .and_then(|entity| chunks.get_mut(entity).ok())
+        else {
+            warn!("no chunk at {position} to apply {change:?}");
+            return None;
+        };
+        let before = cells.get_cell(local.x, local.y, local.z);
+        let after = change.apply(before);
+        cells.set_cell(local.x, local.y, local.z, after);
+        if journal.recording {
+            journal.edits.push(WorldEdit {
+                tick: tick.get(),
+                position,
+                change,
+            });
+        }
+        Some(CellEdit {
+            position,
+            before,
+            after,
+        })
+    };
+
+    for action in std::mem::take(&mut pending.0) {
+        let action = action
+            .into_iter()
+            .filter_map(|(position, change)| apply(position, change))
+            .collect::<Vec<_>>();
+        history.push(action);
+    }
+    // undo and redo go through the journal as exact cells so a replay matches
+    for _ in 0..std::mem::take(&mut history.wants_undo) {
+        let Some(action) = history.undo.pop_back() else {
+            break;
+        };
+        for edit in action.iter().rev() {
+            apply(edit.position, Change::Cell(edit.before));
+        }
+        history.redo.push(action);
+    }
+    for _ in 0..std::mem::take(&mut history.wants_redo) {
+        let Some(action) = history.redo.pop() else {
+            break;
+        };
+        for edit in &action {
+            apply(edit.position, Change::Cell(edit.after));
+        }
+        history.undo.push_back(action);
+    }
+}
+
+/// blocks are written as their id in `block_palette`
+fn insert_change(serde: &mut BinSerializer, change: &Change) -> bevy::ecs::error::Result<()> {
+    // a tag for the kind of change then whatever it carries
+    match change {
+        Change::Block(block) => {
+            serde.push(0);
+            serde.push(*block as u8);
+        }
+        Change::AtK(block, k) => {
+            serde.push(1);
+            serde.push(*block as u8);
+            serde.push_slice(&k.to_be_bytes());
+        }
+        Change::Cell(cell) => {
+            serde.push(2);
+            serde.push(cell.block as u8);
+            for value in [
+                cell.energy,
+                cell.tempreture,
+                cell.density,
+                cell.presure,
+                cell.charge,
+                cell.flux,
+            ] {
+                serde.push_slice(&value.to_be_bytes());
+            }
+            serde.push(cell.flags.bits());
+        }
+        Change::Tempreture(k) => {
+            serde.push(3);
+            serde.push_slice(&k.to_be_bytes());
+        }
+        Change::Energy(energy) => {
+            serde.push(4);
+            serde.push_slice(&energy.to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+/// `blocks` is the journal's palette
+fn extract_change(
+    serde: &mut BinDeSerializer,
+    blocks: &[BlockType],
+) -> bevy::ecs::error::Result<Change> {
+    let tag = serde.extract::<[u8; 1]>()?;
+    let fixed =
+        |serde: &mut BinDeSerializer| serde.extract::<[u8; 4]>().map(FixedNum::from_be_bytes);
+    let block = |serde: &mut BinDeSerializer| -> bevy::ecs::error::Result<BlockType> {
+        let [id] = serde.extract::<[u8; 1]>()?;
+        Ok(blocks
+            .get(id as usize)
+            .copied()
+            .ok_or(chunk_serde::BinError::InvalidId(id))?)
+    };
+    Ok(match tag {
+        [0] => Change::Block(block(serde)?),
+        [1] => {
+            let block = block(serde)?;
+            Change::AtK(block, fixed(serde)?)
+        }
+        [2] => {
+            let block = block(serde)?;
+            let cell = CellData {
+                block,
+                energy: fixed(serde)?,
+                tempreture: fixed(serde)?,
+                density: fixed(serde)?,
+                presure: fixed(serde)?,
+                charge: fixed(serde)?,
+                flux: fixed(serde)?,
+                flags: CellFlags::empty(),
+            };
+            let flags = serde.extract::<[u8; 1]>()?[0];
+            Change::Cell(CellData {
+                flags: CellFlags::from_bits_retain(flags),
+                ..cell
+            })
+        }
+        [3] => Change::Tempreture(fixed(serde)?),
+        [4] => Change::Energy(fixed(serde)?),
+        [tag] => return Err(chunk_serde::BinError::InvalidTag(tag).into()),
+    })
+}
+
+fn start_recording(
+    mut journal: ResMut<Journal>,
+    manager: Res<ChunkManager>,
+    chunks: Query<&Cells>,
+    tick: Res<VoxelTick>,
+    seed: Res<WorldSeed>,
+) {
+    if !journal.wants_start {
+        return;
+    }
+    journal.wants_start = false;
+    match manager.save_world(&chunks, tick.get(), seed.get()) {
+        Ok(start) => {
+            journal.start = start;
+            journal.edits.clear();
+            journal.recording = true;
+            info!("recording edits from tick {}", tick.get());
+        }
+        Err(e) => error!("Failed to snapshot world for journal: {e}"),
+    }
+}
+
+#[test]
+fn journal_round_trip() {
+    let mut journal = Journal {
+        recording: true,
+        start: b"PhoxW".to_vec(),
+        ..Default::default()
+    };
+    let cell = CellData::at_k(BlockType::Copper, FixedNum::lit("350"));
+    let changes = [
+        Change::Block(BlockType::Water),
+        Change::AtK(BlockType::Uranium, FixedNum::lit("600.5")),
+        Change::Cell(cell),
+        Change::Tempreture(FixedNum::lit("1200")),
+        Change::Energy(FixedNum::lit("5000")),
+    ];
+    for (tick, change) in changes.into_iter().enumerate() {
+        journal.edits.push(WorldEdit {
+            tick: tick as u64 + 7,
+            position: IVec3::new(-1, 2, 300),
+            change,
+        });
+    }
+    let data = journal.stop().unwrap();
+    let (replay, start) = Replay::from_journal(&data).unwrap();
+    assert_eq!(start, b"PhoxW");
+    assert_eq!(replay.edits.len(), 5);
+    assert_eq!(replay.edits[0].position, IVec3::new(-1, 2, 300));
+    assert_eq!(replay.edits[0].tick, 7);
+    for (edit, change) in replay.edits.iter().zip(changes) {
+        assert_eq!(edit.change, change);
+    }
+}
+
+#[test]
+fn tempreture_keeps_the_block() {
+    let old = CellData::at_k(BlockType::Water, FixedNum::lit("300"));
+    let hot = Change::Tempreture(FixedNum::lit("500")).apply(old);
+    assert_eq!(hot.block, BlockType::Water);
+    assert_eq!(hot.temperature(), FixedNum::lit("500"));
+    assert!(hot.is_gas());
+    let back = Change::Energy(old.energy).apply(hot);
+    assert_eq!(back.block, BlockType::Water);
+    assert!(!back.is_gas());
+}
+
+#[test]
+fn journal_rejects_other_versions() {
+    let mut data = Journal::default().stop().unwrap();
+    data[5] = JOURNAL_VERSION + 1;
+    assert!(Replay::from_journal(&data).is_err());
+    // a huge edit count in a tiny file fails to read instead of allocating
+    let mut data = Journal::default().stop().unwrap();
+    let len = data.len() - 8;
+    data[len..].copy_from_slice(&u64::MAX.to_be_bytes());
+    assert!(Replay::from_journal(&data).is_err());
+}
+
+#[test]
+fn journal_blocks_follow_its_palette() {
+    let mut serde = BinSerializer::new();
+    serde.insert(b"PhoxJ").unwrap();
+    serde.push(JOURNAL_VERSION);
+    serde.insert(&Palette::new(["Water", "Copper"])).unwrap();
+    serde.insert(&1u64).unwrap();
+    serde.insert(&3u64).unwrap();
+    serde.insert(&ChunkId(IVec3::ZERO)).unwrap();
+    serde.push(0);
+    serde.push(1);
+    let (replay, _) = Replay::from_journal(&serde.finalize()).unwrap();
+    assert_eq!(replay.edits[0].change, Change::Block(BlockType::Copper));
+}
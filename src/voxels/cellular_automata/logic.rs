@@ -8,15 +8,22 @@ use crate::voxels::{
 
 use super::*;
 
-pub fn step<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64) {
-    step_diag(chunk, neighbours, tick);
+pub fn step<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64, seed: u64) {
+    step_diag(chunk, neighbours, tick, seed);
 }
 
 use fastrand::Rng;
 
-pub fn step_diag<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64) -> CellData {
+/// `seed` is the world seed, the same seed tick and world will always give the same result
+pub fn step_diag<'a>(
+    chunk: ChunkIter<'a>,
+    neighbours: ChunkGared<'a>,
+    tick: u64,
+    seed: u64,
+) -> CellData {
     let mut max = CellData::MIN;
     let mut rng = Rng::new();
+    let tick_seed = tick_seed(seed, tick);
     for (id, data) in chunk {
         let Some(mut cell) = neighbours.get(id) else {
             #[cfg(debug_assertions)]
@@ -32,38 +39,99 @@ pub fn step_diag<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64
                 neighbours.root()
             );
         }
+        // charge from the start of the step so flow between cells is symmetric
+        let q1 = cell.charge;
+        // highest gas presure pushing on this cell
+        let mut load = FixedNum::ZERO;
+        // spread of presure and temperature around this cell for rupture checks
+        let mut min_p = FixedNum::MAX;
+        let mut max_p = FixedNum::ZERO;
+        let mut max_dt = FixedNum::ZERO;
+        // neutrons arriving from the neighbours and how many of them were slowed down
+        let mut incoming = FixedNum::ZERO;
+        let mut moderated = FixedNum::ZERO;
         for neighbour_id in id.neighbours() {
             let Some(neighbour_data) = neighbours.get(neighbour_id) else {
                 continue; // skip if neighbour is out of bounds
             };
+            if neighbour_data.is_gas() {
+                load = load.max(neighbour_data.pressure());
+            }
+            // the void past the edge of the map has no presure to push back with
+            if neighbour_data.get_block_type() != BlockType::Void {
+                min_p = min_p.min(neighbour_data.pressure());
+                max_p = max_p.max(neighbour_data.pressure());
+            }
             let t1 = cell.temperature();
             let t2 = neighbour_data.temperature();
             let delta_t = t2 - t1;
+            max_dt = max_dt.max(delta_t.abs());
+
+            // every cell spreads its flux evenly to its neighbours
+            let share = neighbour_data.flux / SUM_DIVISOR;
+            incoming = incoming.saturating_add(share);
+            moderated = moderated
+                .saturating_add(share.saturating_mul(neighbour_data.properties().moderation));
             let g = cell.lookup_g(neighbour_data.get_block_type());
             let heat_transfer = g * delta_t;
             cell.energy = cell.energy.saturating_add(heat_transfer);
+
+            let e = cell.lookup_e(neighbour_data.get_block_type());
+            if e != FixedNum::ZERO {
+                let voltage = neighbour_data.charge.saturating_sub(q1);
+                let current = e.saturating_mul(voltage) / SUM_DIVISOR;
+                cell.charge = cell.charge.saturating_add(current);
+                // resistive heating P = I * V
+                let heat = current.saturating_mul(voltage).abs();
+                cell.energy = cell
+                    .energy
+                    .saturating_add(heat.saturating_mul(JOULE_HEATING));
+            }
             cell.set_tempreture();
         }
-        let b = cell.get_block_type();
-        if b == BlockType::Uranium {
-            cell.energy = cell.energy.saturating_add(FixedNum::lit("3000.")); // hack to add uranium heat without changing my meta code
-        } else if b == BlockType::Thorium {
-            cell.energy = cell.energy.saturating_add(FixedNum::lit("1500.")); // hack to add thorium heat without changing my meta code
+        cell.energy = cell.energy.saturating_add(cell.properties().generating);
+        do_fission(&mut cell, incoming, moderated);
+        let mean_life = cell.properties().mean_life();
+        if mean_life != 0 {
+            // seed from the cell so every fuel block doesn't decay on the same tick
+            rng.seed(
+                tick_seed
+                    ^ ((cell.energy.to_bits() as u64) << 32)
+                    ^ (id.x + id.y * CHUNK_SIZE + id.z * CHUNK_SIZE * CHUNK_SIZE) as u64,
+            );
+            if rng.u32(0..mean_life) == 0 {
+                cell.set_block_type(decay_product(&cell));
+            }
         }
-        cell.flags.remove(CellFlags::MOVE_ALL);
+        cell.flags.remove(CellFlags::MOVE_ALL | CellFlags::RUPTURED);
         match tick & 0b11 {
             0b00 => {
                 cell.set_phase();
+                cell.set_presure(load);
+                let stress = max_p
+                    .saturating_sub(min_p)
+                    .max(max_dt.saturating_mul(THERMAL_STRESS));
+                if is_rupture(&cell, stress) {
+                    // the block breaks apart leaving a breach
+                    cell.set_block_type(BlockType::Air);
+                    cell.flags |= CellFlags::RUPTURED;
+                }
             }
             0b01 => {
                 if cell.temperature() < FixedNum::lit("0.0") {
-                    println!("how? {:?} {:?} {}", id, cell.temperature(), cell.energy);
+                    warn!(
+                        "cell {:?} is below absolute zero: {} with {} energy",
+                        id,
+                        cell.temperature(),
+                        cell.energy
+                    );
                 }
                 cell.set_density();
                 cell.flags |= check_gravity(id, &cell, &neighbours);
             }
             0b10 => {
                 cell.set_phase();
+                cell.set_presure(load);
                 if cell.can_move() {
                     match (tick >> 2) & 0b111 {
                         0b000 => {
@@ -78,11 +146,12 @@ pub fn step_diag<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64
                         0b110 => {
                             cell.flags |= do_brownian(id, id.z & 1 == 0, false, &cell, &neighbours);
                         }
-                        0b001 => {
-                            cell.flags |= do_brownian_gas(id, id.y & 1 == 1, &cell, &neighbours);
+                        0b001 | 0b101 => {
+                            cell.flags |= do_presure_flow(id, &cell, &neighbours);
                         }
                         _ => {
-                            rng.seed(tick ^ id.y as u64);
+                            // every cell in a layer must pick the same way so swaps line up
+                            rng.seed(tick_seed ^ id.y as u64);
                             let odd = rng.i32(0..=1);
                             if rng.bool() {
                                 cell.flags |=
@@ -97,7 +166,12 @@ pub fn step_diag<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64
             }
             0b11 => {
                 if cell.temperature() < FixedNum::lit("0.0") {
-                    println!("how? {:?} {:?} {}", id, cell.temperature(), cell.energy);
+                    warn!(
+                        "cell {:?} is below absolute zero: {} with {} energy",
+                        id,
+                        cell.temperature(),
+                        cell.energy
+                    );
                 }
                 cell.set_density();
                 cell.flags |= check_gravity(id, &cell, &neighbours);
@@ -111,6 +185,46 @@ pub fn step_diag<'a>(chunk: ChunkIter<'a>, neighbours: ChunkGared<'a>, tick: u64
     max
 }
 
+/// Absorb some of the `incoming` flux, fuel splits and gives off heat and more neutrons
+/// `moderated` neutrons are slower so they are better at splitting fuel
+fn do_fission(cell: &mut CellData, incoming: FixedNum, moderated: FixedNum) {
+    let props = cell.properties();
+    let mut flux = incoming
+        .saturating_mul(NEUTRON_RETAIN)
+        .saturating_add(props.neutron_source);
+    let absorbed = flux.saturating_mul(props.absorption);
+    flux -= absorbed;
+    if props.fission_yield != FixedNum::ZERO {
+        let splits = absorbed.saturating_add(
+            moderated
+                .saturating_mul(NEUTRON_RETAIN)
+                .saturating_mul(props.absorption),
+        );
+        cell.energy = cell
+            .energy
+            .saturating_add(splits.saturating_mul(props.fission_heat));
+        flux = flux.saturating_add(splits.saturating_mul(props.fission_yield));
+    }
+    cell.flux = flux.clamp(FixedNum::ZERO, FLUX_MAX);
+}
+
+/// Spread the world seed and tick over all the bits so nearby ticks don't share rolls
+pub fn tick_seed(seed: u64, tick: u64) -> u64 {
+    seed ^ tick.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+fn decay_product(cell: &CellData) -> BlockType {
+    BlockType::from_repr(cell.properties().decay_product).unwrap_or(BlockType::Air)
+}
+
+/// Only solids can rupture, liquids and gases just flow
+fn is_rupture(cell: &CellData, stress: FixedNum) -> bool {
+    if cell.can_move() {
+        return false;
+    }
+    stress > cell.properties().strength
+}
+
 fn check_gravity(id: CellId, cell: &CellData, neighbours: &ChunkGared) -> CellFlags {
     if !cell.can_move() {
         // check if the cell can move
@@ -146,23 +260,44 @@ fn check_gravity(id: CellId, cell: &CellData, neighbours: &ChunkGared) -> CellFl
     CellFlags::empty()
 }
 
-fn do_brownian_gas(id: CellId, odd: bool, cell: &CellData, neighbours: &ChunkGared) -> CellFlags {
-    let (target, will_move) = match odd {
-        true => (id.up(), CellFlags::MOVE_UP),
-        false => (id.down(), CellFlags::MOVE_DOWN),
-    };
-    let Some(other) = neighbours.get(target) else {
-        return CellFlags::empty();
-    };
-    if !other.can_move() {
+/// Push a gas towards the neighbouring gas with the biggest presure difference
+/// both cells pick each other from the same values so the swap is agreed on
+fn do_presure_flow(id: CellId, cell: &CellData, neighbours: &ChunkGared) -> CellFlags {
+    if !cell.is_gas() {
         return CellFlags::empty();
     }
-    if cell.is_gas() && other.is_gas() {
-        will_move
-    } else {
-        // gases cannot move into liquids and vice versa
-        CellFlags::empty()
+    // compare last steps values, the neighbours can't see this steps
+    let Some(old) = neighbours.get(id) else {
+        return CellFlags::empty();
+    };
+    // push into the lowest neighbour, only a cell with nowhere lower to go takes gas in
+    // from its highest neighbour, so a swap always moves gas from high to low presure
+    let mut push = (PRESURE_FLOW_MIN, CellFlags::empty());
+    let mut take = (PRESURE_FLOW_MIN, CellFlags::empty());
+    for (target, direction) in [
+        (id.up(), CellFlags::MOVE_UP),
+        (id.down(), CellFlags::MOVE_DOWN),
+        (id.left(), CellFlags::MOVE_LEFT),
+        (id.right(), CellFlags::MOVE_RIGHT),
+        (id.forward(), CellFlags::MOVE_FORWARD),
+        (id.backward(), CellFlags::MOVE_BACK),
+    ] {
+        let Some(other) = neighbours.get(target) else {
+            continue;
+        };
+        if !other.is_gas() {
+            // gases only flow into other gases
+            continue;
+        }
+        let diff = old.pressure() - other.pressure();
+        if diff > push.0 {
+            push = (diff, direction);
+        }
+        if -diff > take.0 {
+            take = (-diff, direction);
+        }
     }
+    if push.1.is_empty() { take.1 } else { push.1 }
 }
 
 fn do_brownian(
@@ -31,3 +31,107 @@ fn water_is_more_dense_than_air() {
     );
     assert!(water.properties().density > air.properties().density);
 }
+
+#[test]
+fn hot_air_has_more_presure() {
+    let mut air = CellData::at_k(
+        crate::voxels::block::BlockType::Air,
+        FixedNum::lit("293.15"),
+    );
+    air.set_density();
+    air.set_presure(FixedNum::ZERO);
+    assert!((air.pressure() - crate::voxels::cellular_automata::ATM_1).abs() < FixedNum::ONE);
+
+    let mut hot = CellData::at_k(crate::voxels::block::BlockType::Air, FixedNum::lit("586.3"));
+    hot.set_density();
+    hot.set_presure(FixedNum::ZERO);
+    assert!(hot.pressure() > air.pressure());
+}
+
+#[test]
+fn fuel_decays_into_spent_fuel() {
+    use crate::voxels::block::BlockType;
+    for fuel in [BlockType::Uranium, BlockType::Thorium] {
+        let props = fuel.properties();
+        assert!(props.generating > FixedNum::ZERO);
+        assert!(props.mean_life() > props.half_life);
+        assert_eq!(props.decay_product, BlockType::SpentFuel as u8);
+    }
+    assert_eq!(BlockType::SpentFuel.properties().mean_life(), 0);
+}
+
+#[test]
+fn moderation_makes_more_heat() {
+    use crate::voxels::block::BlockType;
+    let fuel = CellData::at_k(BlockType::Uranium, FixedNum::lit("293.15"));
+    let mut bare = fuel;
+    super::do_fission(&mut bare, FixedNum::lit("100"), FixedNum::ZERO);
+    let mut moderated = fuel;
+    super::do_fission(&mut moderated, FixedNum::lit("100"), FixedNum::lit("150"));
+    assert!(moderated.energy > bare.energy);
+    assert!(moderated.flux > bare.flux);
+
+    let mut rod = CellData::at_k(BlockType::ControlRod, FixedNum::lit("293.15"));
+    super::do_fission(&mut rod, FixedNum::lit("100"), FixedNum::ZERO);
+    assert!(rod.flux < bare.flux);
+}
+
+/// Run one step of a chunk with nothing around it, tick 0 is a rupture check
+fn step_alone(
+    prev: &crate::voxels::cellular_automata::Cells,
+) -> crate::voxels::cellular_automata::Cells {
+    use crate::voxels::cellular_automata::{ChunkGared, ChunkIter};
+    let mut next = prev.clone();
+    let chunks = [Some(prev), None, None, None, None, None, None];
+    #[cfg(debug_assertions)]
+    let neighbours = ChunkGared::new(chunks, crate::voxels::ChunkId::new(0, 0, 0));
+    #[cfg(not(debug_assertions))]
+    let neighbours = ChunkGared::new(chunks);
+    super::step_diag(ChunkIter::new(&mut next), neighbours, 0, 0);
+    next
+}
+
+#[test]
+fn hot_gas_breaks_wax() {
+    use crate::voxels::{block::BlockType, cellular_automata::Cells};
+    for (k, breaks) in [("293.15", false), ("2000", true)] {
+        let mut prev = Cells::solid(CellData::at_k(BlockType::Air, FixedNum::lit(k)));
+        prev.set_cell(
+            1,
+            1,
+            1,
+            CellData::at_k(BlockType::Wax, FixedNum::lit("293.15")),
+        );
+        let wax = step_alone(&prev).get_cell(1, 1, 1);
+        assert_eq!(
+            wax.get_block_type() == BlockType::Air,
+            breaks,
+            "air at {k}K"
+        );
+        assert_eq!(
+            wax.flags
+                .contains(crate::voxels::cellular_automata::CellFlags::RUPTURED),
+            breaks
+        );
+    }
+}
+
+#[test]
+fn charge_flows_through_copper_not_rubber() {
+    use crate::voxels::{block::BlockType, cellular_automata::Cells};
+    let room = FixedNum::lit("293.15");
+    let mut prev = Cells::solid(CellData::at_k(BlockType::Air, room));
+    let mut charged = CellData::at_k(BlockType::Copper, room);
+    charged.charge = FixedNum::lit("100");
+    prev.set_cell(1, 1, 1, charged);
+    prev.set_cell(2, 1, 1, CellData::at_k(BlockType::Copper, room));
+    prev.set_cell(1, 1, 2, CellData::at_k(BlockType::Rubber, room));
+    let next = step_alone(&prev);
+    let source = next.get_cell(1, 1, 1);
+    assert!(source.charge < charged.charge);
+    assert!(next.get_cell(2, 1, 1).charge > FixedNum::ZERO);
+    assert_eq!(next.get_cell(1, 1, 2).charge, FixedNum::ZERO);
+    // the current heats the copper it goes through
+    assert!(source.energy > charged.energy);
+    assert!(next.get_cell(2, 1, 1).temperature() > room);
+}
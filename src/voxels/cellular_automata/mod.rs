@@ -1,7 +1,14 @@
 mod batching;
+mod breakpoint;
 mod cells;
 mod consts;
+mod hash;
+mod history;
+mod journal;
 mod logic;
+mod plan;
+mod rupture;
+mod speed;
 mod util;
 
 use crate::voxels::VoidNeighbours;
@@ -11,21 +18,54 @@ pub use batching::{
     can_modify_world,
 };
 use bevy::prelude::*;
+pub use breakpoint::{BreakHit, Breakpoint, Breakpoints};
 pub use cells::{CellData, CellFlags};
 pub use consts::*;
+pub use hash::{StateHashes, first_divergence, hash_world};
+pub use history::{CellEdit, History};
+pub use journal::{Change, Journal, PendingEdits, Replay, WorldEdit};
 pub use logic::{StepMode, step};
+pub use plan::BatchingPlan;
+pub use rupture::BlockRuptured;
+pub use speed::{MAX_SCALE, MIN_SCALE, SimSpeed, Speed, TICKS_PER_STEP};
 pub use util::*;
 
 mod debugging;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins(batching::plugin);
+    app.add_plugins((
+        batching::plugin,
+        rupture::plugin,
+        hash::plugin,
+        journal::plugin,
+        history::plugin,
+        breakpoint::plugin,
+    ));
     #[cfg(debug_assertions)]
     app.add_plugins(debugging::plugin);
+    resources(app);
+}
+
+/// Steps the whole world once per `Update` with no input or rendering
+pub fn headless_plugin(app: &mut App) {
+    app.add_plugins((
+        batching::headless_plugin,
+        rupture::plugin,
+        hash::plugin,
+        journal::plugin,
+    ));
+    resources(app);
+}
+
+fn resources(app: &mut App) {
     app.init_resource::<VoxelTick>()
         .init_resource::<TargetTick>()
+        .init_resource::<WorldSeed>()
+        .init_resource::<SimSpeed>()
         .register_type::<VoxelTick>()
-        .register_type::<TargetTick>();
+        .register_type::<TargetTick>()
+        .register_type::<WorldSeed>()
+        .register_type::<SimSpeed>();
     app.init_resource::<VoidNeighbours>();
 }
 
@@ -35,6 +75,20 @@ pub struct VoxelTick(u64);
 #[derive(Resource, Default, Reflect)]
 pub struct TargetTick(u64);
 
+/// Seed for everything random in the automaton, saved with the world
+#[derive(Resource, Default, Reflect, Clone, Copy)]
+pub struct WorldSeed(u64);
+
+impl WorldSeed {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
 impl VoxelTick {
     pub fn new(tick: u64) -> Self {
         Self(tick)
@@ -65,8 +119,8 @@ impl TargetTick {
         self.0 += 1;
     }
 
+    /// Jump the target, used to drop a backlog of ticks when the speed changes
     pub fn set(&mut self, to: u64) {
-        warn!("Target Time was set; only do this for testing at the moment!");
         self.0 = to;
     }
 }
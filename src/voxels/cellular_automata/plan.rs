@@ -0,0 +1,160 @@
+use bevy::{platform::time::Instant, prelude::*};
+
+/// Fewest chunks worth putting in a batch of there own
+pub const MIN_BATCH: usize = 10;
+
+/// What the batches were planned from and how the last tick actually went.
+/// All times are in ms, same as `TARGET_TICKTIME`
+#[derive(Resource, Default, Debug)]
+pub struct BatchingPlan {
+    /// frame time with the stepping taken off
+    pub base_frame: f64,
+    /// how long stepping every chunk once should take across all threads
+    pub tick_cost: f64,
+    /// how many chunks step at once, measured from the last tick
+    pub parallelism: f64,
+    /// planned cost and chunk count of each batch
+    pub planned: Vec<(f64, usize)>,
+    /// wall time each batch took last tick
+    pub measured: Vec<f64>,
+    /// ms from starting the last tick to it being applied
+    pub last_tick: f64,
+    /// ticks that took more than 10% over `target`
+    pub overruns: u64,
+    /// how long each tick was planned to take, 0 when running as fast as it can
+    pub target: f64,
+    started: Option<Instant>,
+}
+
+impl BatchingPlan {
+    pub(super) fn start_tick(&mut self) {
+        self.started = Some(Instant::now());
+        self.measured.clear();
+    }
+
+    pub(super) fn finish_tick(&mut self) {
+        let Some(started) = self.started.take() else {
+            return;
+        };
+        self.last_tick = started.elapsed().as_secs_f64() * 1000.;
+        if self.overran() {
+            self.overruns += 1;
+        }
+    }
+
+    /// Record a batch taking `wall` ms, `serial` is the sum of its chunks own step times
+    pub(super) fn measure_batch(&mut self, wall: f64, serial: f64) {
+        self.measured.push(wall);
+        if wall > 0. && serial > 0. {
+            let parallelism = (serial / wall).max(1.);
+            self.parallelism = if self.parallelism == 0. {
+                parallelism
+            } else {
+                self.parallelism * 0.9 + parallelism * 0.1
+            };
+        }
+    }
+
+    /// ms since the current tick started, 0 between ticks
+    pub fn running_for(&self) -> f64 {
+        self.started
+            .map_or(0., |started| started.elapsed().as_secs_f64() * 1000.)
+    }
+
+    /// with no target there's nothing to overrun
+    pub fn overran(&self) -> bool {
+        self.target > 0. && self.last_tick > self.target * 1.1
+    }
+
+    /// The last tick was far enough off `target` to be worth replanning
+    pub fn is_off_target(&self) -> bool {
+        self.target > 0.
+            && self.last_tick != 0.
+            && (self.last_tick - self.target).abs() > self.target * 0.1
+    }
+
+    fn mean_measured(&self) -> f64 {
+        if self.measured.is_empty() {
+            return 0.;
+        }
+        self.measured.iter().sum::<f64>() / self.measured.len() as f64
+    }
+
+    /// Split `costs` into batches so every batch costs about the same
+    /// and all of them fit in `tick_time` with a frame spare to apply the tick.
+    /// A `tick_time` of 0 puts everything in one batch
+    pub(super) fn plan(
+        &mut self,
+        frame_time: f64,
+        tick_time: f64,
+        costs: Vec<(Entity, Option<f64>)>,
+    ) -> Vec<Vec<Entity>> {
+        // frames that step include a batch, take it back off to see what the rest of the game costs
+        self.base_frame = (frame_time - self.mean_measured()).max(1.);
+        if self.parallelism == 0. {
+            self.parallelism = std::thread::available_parallelism().map_or(1., |n| n.get() as f64);
+        }
+
+        // chunks that have never been stepped are guessed at the average
+        let known = costs
+            .iter()
+            .filter_map(|(_, cost)| *cost)
+            .collect::<Vec<_>>();
+        let guess = if known.is_empty() {
+            0.
+        } else {
+            known.iter().sum::<f64>() / known.len() as f64
+        };
+        let costs = costs
+            .into_iter()
+            .map(|(entity, cost)| (entity, cost.unwrap_or(guess)))
+            .collect::<Vec<_>>();
+        self.tick_cost = costs.iter().map(|(_, cost)| cost).sum::<f64>() / self.parallelism;
+
+        // each batch gets its own frame, then one more to apply the tick
+        self.target = tick_time;
+        let frames = ((tick_time - self.tick_cost) / self.base_frame).floor() as usize;
+        let most = (costs.len() / MIN_BATCH).max(1);
+        let batches = frames.saturating_sub(1).clamp(1, most);
+
+        let (groups, planned) = balance(costs, batches);
+        self.planned = planned;
+        groups
+    }
+}
+
+/// Most expensive chunk first into the cheapest batch so far,
+/// it's not the best split but its never more than a third off
+pub fn balance(
+    mut costs: Vec<(Entity, f64)>,
+    batches: usize,
+) -> (Vec<Vec<Entity>>, Vec<(f64, usize)>) {
+    costs.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut groups = vec![Vec::new(); batches];
+    let mut planned = vec![(0., 0); batches];
+    for (entity, cost) in costs {
+        let cheapest = planned
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.0.total_cmp(&b.1.0).then(a.1.1.cmp(&b.1.1)))
+            .map_or(0, |(i, _)| i);
+        groups[cheapest].push(entity);
+        planned[cheapest].0 += cost;
+        planned[cheapest].1 += 1;
+    }
+    (groups, planned)
+}
+
+#[test]
+fn balance_splits_by_cost() {
+    let costs = [8., 7., 6., 5., 4., 3., 2., 1.]
+        .into_iter()
+        .enumerate()
+        .map(|(i, cost)| (Entity::from_raw(i as u32), cost))
+        .collect::<Vec<_>>();
+    let (groups, planned) = balance(costs, 3);
+    assert_eq!(groups.iter().map(|g| g.len()).sum::<usize>(), 8);
+    let most = planned.iter().map(|p| p.0).fold(0., f64::max);
+    let least = planned.iter().map(|p| p.0).fold(f64::MAX, f64::min);
+    assert!(most - least <= 2., "{planned:?}");
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use super::*;
+use crate::{
+    GameState,
+    voxels::{ChunkId, block::BlockType},
+};
+
+const CHUNK_SIZE: i32 = crate::voxels::map::CHUNK_SIZE;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<BlockRuptured>();
+    // NextStep has the new state until set_prev swaps it in
+    app.add_systems(
+        Update,
+        find_ruptures
+            .in_set(ApplyStep::PreApply)
+            .run_if(in_state(GameState::Game)),
+    );
+}
+
+/// Sent when a solid cell breaks under presure or temperature stress
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BlockRuptured {
+    pub chunk: ChunkId,
+    /// position of the cell inside the chunk
+    pub cell: IVec3,
+    /// what the cell was before it broke
+    pub block: BlockType,
+}
+
+impl BlockRuptured {
+    /// position of the cell in the world
+    pub fn position(&self) -> IVec3 {
+        self.chunk.0 * CHUNK_SIZE + self.cell
+    }
+}
+
+fn find_ruptures(
+    chunks: Query<(&ChunkId, &Cells, &NextStep)>,
+    mut events: EventWriter<BlockRuptured>,
+) {
+    for (id, prev, next) in &chunks {
+        if !next.has_run || next.is_asleep() {
+            continue;
+        }
+        // the step marks the cells it broke, decay can turn blocks to air too
+        for (i, (old, new)) in prev.blocks().zip(next.chunk.blocks()).enumerate() {
+            if !new.flags.contains(CellFlags::RUPTURED) {
+                continue;
+            }
+            events.write(BlockRuptured {
+                chunk: *id,
+                cell: Cells::position(i),
+                block: old.get_block_type(),
+            });
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+
+use crate::TARGET_TICKTIME;
+
+/// Slowest and fastest the simulation can be scaled to
+pub const MIN_SCALE: f64 = 0.25;
+pub const MAX_SCALE: f64 = 8.;
+
+/// Ticks the target moves each `TARGET_TICKTIME` fixed step at 1x,
+/// 3 is how fast the simulation ran before it could be scaled
+pub const TICKS_PER_STEP: f64 = 3.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub enum Speed {
+    /// times `TICKS_PER_STEP` ticks per `TARGET_TICKTIME`, 1 is normal speed
+    Scale(f64),
+    /// start a new tick as soon as the last one is applied
+    Unlimited,
+}
+
+/// How fast the automaton runs, changed at runtime from the console or the speed panel
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+pub struct SimSpeed {
+    speed: Speed,
+    paused: bool,
+    /// ticks still to run while paused
+    steps: u64,
+}
+
+impl Default for SimSpeed {
+    fn default() -> Self {
+        SimSpeed {
+            speed: Speed::Scale(1.),
+            paused: false,
+            steps: 0,
+        }
+    }
+}
+
+impl SimSpeed {
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.speed == Speed::Unlimited
+    }
+
+    /// also drops any steps still to run, so a breakpoint stops a long step where it hit
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.steps = 0;
+    }
+
+    /// also drops any steps that haven't run yet
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.steps = 0;
+    }
+
+    /// Run `n` more ticks then stop, pauses if it wasn't already
+    pub fn step(&mut self, n: u64) {
+        self.paused = true;
+        self.steps = self.steps.saturating_add(n);
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Clamped to `MIN_SCALE..=MAX_SCALE`
+    pub fn set_scale(&mut self, scale: f64) {
+        self.speed = Speed::Scale(scale.clamp(MIN_SCALE, MAX_SCALE));
+    }
+
+    pub fn set_unlimited(&mut self) {
+        self.speed = Speed::Unlimited;
+    }
+
+    /// Multiple of normal speed, `None` when there's no limit
+    pub fn scale(&self) -> Option<f64> {
+        match self.speed {
+            Speed::Scale(scale) => Some(scale),
+            Speed::Unlimited => None,
+        }
+    }
+
+    /// ms a tick has to finish in, 0 when there's no limit
+    pub fn tick_time(&self) -> f64 {
+        match self.speed {
+            Speed::Scale(scale) => TARGET_TICKTIME / (scale * TICKS_PER_STEP),
+            Speed::Unlimited => 0.,
+        }
+    }
+
+    /// Takes one pending step if paused, true if a tick should start
+    pub(super) fn take_step(&mut self) -> bool {
+        if self.steps == 0 {
+            return false;
+        }
+        self.steps -= 1;
+        true
+    }
+}
+
+impl std::fmt::Display for SimSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.paused {
+            return match self.steps {
+                0 => write!(f, "paused"),
+                n => write!(f, "paused, {n} steps to go"),
+            };
+        }
+        match self.speed {
+            Speed::Scale(scale) => write!(f, "{scale}x"),
+            Speed::Unlimited => write!(f, "max"),
+        }
+    }
+}
+
+#[test]
+fn steps_only_count_down_when_taken() {
+    let mut speed = SimSpeed::default();
+    speed.step(2);
+    assert!(speed.is_paused());
+    assert!(speed.take_step());
+    assert!(speed.take_step());
+    assert!(!speed.take_step());
+    speed.set_scale(100.);
+    assert_eq!(speed.scale(), Some(MAX_SCALE));
+    assert_eq!(
+        speed.tick_time(),
+        TARGET_TICKTIME / (MAX_SCALE * TICKS_PER_STEP)
+    );
+}
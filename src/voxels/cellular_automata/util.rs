@@ -7,7 +7,11 @@ use block_meta::FixedNum;
 
 #[cfg(debug_assertions)]
 use crate::voxels::ChunkId;
-use crate::voxels::{block::BlockType, cellular_automata::CellData, map::ChunkData};
+use crate::voxels::{
+    block::BlockType,
+    cellular_automata::{CellData, CellFlags, SLEEP_AFTER, SLEEP_DELTA},
+    map::ChunkData,
+};
 const CHUNK_SIZE: i32 = crate::voxels::map::CHUNK_SIZE;
 pub type Cells = crate::voxels::Chunk<CellData>;
 
@@ -15,6 +19,18 @@ pub type Cells = crate::voxels::Chunk<CellData>;
 pub struct NextStep {
     pub has_run: bool,
     pub(super) chunk: Cells,
+    // steps in a row where nothing much happened
+    idle: u16,
+    // the last step changed something, so the neighbours need to be awake too
+    active: bool,
+    // not stepped this tick, `chunk` is out of date and `Cells` is still right
+    asleep: bool,
+    // a new state was swapped in that the mesh hasn't been checked against
+    pub(super) unmeshed: bool,
+    // swapped in at the end of this tick, `chunk` holds the cells from before it ran
+    pub(super) swapped: bool,
+    // ms it takes to step, averaged over the last few ticks
+    cost: Option<f64>,
 }
 
 impl Default for NextStep {
@@ -22,6 +38,12 @@ impl Default for NextStep {
         NextStep {
             has_run: false,
             chunk: Cells::solid(CellData::THE_VOID),
+            idle: 0,
+            active: false,
+            asleep: false,
+            unmeshed: false,
+            swapped: false,
+            cost: None,
         }
     }
 }
@@ -31,12 +53,68 @@ impl NextStep {
     /// Returns `None` if the step has not been simulated.
     /// If you changed it before it ran your change would be overwritten.
     pub fn borrow_mut(&mut self) -> Option<&mut Cells> {
-        if !self.has_run {
+        if !self.has_run || self.asleep {
             None
         } else {
             Some(&mut self.chunk)
         }
     }
+
+    /// Sleeping chunks are skipped till a neighbour changes or they get edited
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// ms this chunk takes to step, `None` if it never has
+    pub fn cost(&self) -> Option<f64> {
+        self.cost
+    }
+
+    pub(super) fn record_cost(&mut self, ms: f64) {
+        self.cost = Some(self.cost.map_or(ms, |cost| cost * 0.8 + ms * 0.2));
+    }
+
+    pub fn wake(&mut self) {
+        self.idle = 0;
+        self.asleep = false;
+    }
+
+    /// Compare the step that just ran with the state it ran from
+    pub(super) fn settle(&mut self, prev: &Cells) {
+        self.active = !is_idle(prev, &self.chunk);
+        self.idle = if self.active {
+            0
+        } else {
+            self.idle.saturating_add(1)
+        };
+    }
+
+    /// Once the new state is in place, a chunk that has been idle long enough goes to sleep
+    pub(super) fn try_sleep(&mut self) {
+        self.asleep = self.idle >= SLEEP_AFTER;
+        if self.asleep {
+            self.active = false;
+        }
+    }
+}
+
+/// Nothing moved, changed type or heated up by more than `SLEEP_DELTA`,
+/// and nothing in it makes heat or neutrons on its own
+fn is_idle(prev: &Cells, next: &Cells) -> bool {
+    prev.blocks().zip(next.blocks()).all(|(old, new)| {
+        let props = new.properties();
+        old.get_block_type() == new.get_block_type()
+            && !new.flags.intersects(CellFlags::MOVE_ALL)
+            && (new.temperature() - old.temperature()).abs() <= SLEEP_DELTA
+            && new.flux == FixedNum::ZERO
+            && props.generating == FixedNum::ZERO
+            && props.neutron_source == FixedNum::ZERO
+            && props.mean_life() == 0
+    })
 }
 
 #[derive(Clone, Copy, Deref, DerefMut, Debug, PartialEq, Eq, Hash)]
@@ -357,3 +435,28 @@ impl<'a> MutChunkGared<'a> {
         self.set(b, a_index);
     }
 }
+
+#[test]
+fn idle_chunks_fall_asleep() {
+    let cold = || Cells::solid(CellData::at_k(BlockType::Copper, FixedNum::lit("293.15")));
+    let mut next = NextStep {
+        chunk: cold(),
+        ..Default::default()
+    };
+    for _ in 0..SLEEP_AFTER {
+        assert!(!next.is_asleep());
+        next.settle(&cold());
+        next.try_sleep();
+    }
+    assert!(next.is_asleep());
+    assert!(!next.is_active());
+
+    next.wake();
+    let mut hot = cold();
+    hot.set_not_solid();
+    hot.set_by_index(0, CellData::at_k(BlockType::Copper, FixedNum::lit("600")));
+    next.settle(&hot);
+    next.try_sleep();
+    assert!(next.is_active());
+    assert!(!next.is_asleep());
+}
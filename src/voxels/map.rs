@@ -1,16 +1,14 @@
 use bevy::prelude::*;
 use noise::{MultiFractal, NoiseFn};
 use phoxels::{PhoxelsPlugin, core::PhoxelGenerator};
-use strum::EnumCount;
 
 use crate::{
     GameState,
-    utils::BlockIter,
     voxels::{
         BlockType, VoxleMaterialHandle,
         cellular_automata::{self, Cells},
         spawn_test,
-        voxel_chunk::{ChunkId, chunk::ChunkManager, prefab::ChunkPrefabLoader},
+        voxel_chunk::{ChunkId, blueprint, chunk::ChunkManager, prefab, stream},
     },
 };
 
@@ -20,14 +18,67 @@ pub const CHUNK_SIZE: i32 = 10;
 pub const CHUNK_AREA: i32 = CHUNK_SIZE * CHUNK_SIZE;
 pub const CHUNK_VOL: usize = (CHUNK_AREA * CHUNK_SIZE) as usize;
 
+/// Blocks the terrain is made of, anything made by people or the reactor never shows up on its own
+const NATURAL_BLOCKS: [BlockType; 9] = [
+    BlockType::Air,
+    BlockType::Copper,
+    BlockType::Iron,
+    BlockType::Steel,
+    BlockType::Uranium,
+    BlockType::Water,
+    BlockType::Thorium,
+    BlockType::Wax,
+    BlockType::Rubber,
+];
+
 pub fn map_plugin(app: &mut App) {
-    let noise = MapNoise::new();
-    app.init_asset_loader::<ChunkPrefabLoader>()
+    app.add_plugins((prefab::plugin, blueprint::plugin, stream::plugin))
         .init_resource::<ChunkManager>()
-        .add_systems(OnEnter(GameState::Game), spawn_test)
+        .add_systems(
+            OnEnter(GameState::Game),
+            (seed_new_world, set_generator, spawn_test).chain(),
+        )
+        // a loaded world brings its own seed, chunks streamed in after have to match it
+        .add_systems(
+            Update,
+            set_generator.run_if(resource_changed::<cellular_automata::WorldSeed>),
+        )
         .add_plugins(PhoxelsPlugin::<BlockType, ChunkId>::default());
 
-    app.insert_resource(PhoxelGenerator::new(move |id: ChunkId| {
+    // replaced once the world has a seed
+    app.insert_resource(generator(0));
+
+    app.add_plugins(cellular_automata::plugin);
+    app.init_resource::<super::VoxleMaterialHandle>();
+    app.world_mut()
+        .register_component_hooks::<ChunkData>()
+        .on_add(|mut world, ctx| {
+            if world.get::<Cells>(ctx.entity).is_some() {
+                return; // the mesh data was made from these cells
+            }
+            let blocks = world
+                .get::<ChunkData>(ctx.entity)
+                .expect("Just inserted ChunkData")
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            let mut chunk = Cells::empty();
+            for (i, block) in blocks.into_iter().enumerate() {
+                chunk.get_by_index_mut(i).set_block_type(block);
+            }
+            world.commands().entity(ctx.entity).insert(chunk);
+        });
+    app.add_systems(Update, add_mesh_data_to_loaded_chunks);
+    app.add_systems(
+        PostUpdate,
+        super::remove_evaluation.run_if(crate::voxels::cellular_automata::can_modify_next_step),
+    );
+}
+
+/// Terrain for the world with this seed, the same seed always gives the same chunks
+fn generator(seed: u64) -> PhoxelGenerator<BlockType, ChunkId> {
+    let noise = MapNoise::new(seed);
+    PhoxelGenerator::new(move |id: ChunkId| {
         let noise = noise.clone();
         let mut chunk = ChunkData::new(UVec3::splat(CHUNK_SIZE as u32));
         for x in 0..CHUNK_SIZE {
@@ -36,7 +87,7 @@ pub fn map_plugin(app: &mut App) {
                 let gz = id.z * CHUNK_SIZE + z;
                 let h = noise.get_ground(gx, gz);
                 let start_y = id.y * CHUNK_SIZE;
-                let num_blocks = BlockType::COUNT as f64 - 1.;
+                let num_blocks = NATURAL_BLOCKS.len() as f64;
                 // if start_y > h {
                 //     for y in 0..CHUNK_SIZE {
                 //         chunk.set_block(x as u32, y as u32, z as u32, Blocks::Air);
@@ -59,8 +110,8 @@ pub fn map_plugin(app: &mut App) {
                         BlockType::Air
                     } else {
                         let r = ((noise.sample(gx, y + start_y, gz) * num_blocks * 3.) % num_blocks)
-                            as u8;
-                        BlockType::from_repr(r).unwrap_or_default()
+                            as usize;
+                        NATURAL_BLOCKS.get(r).copied().unwrap_or_default()
                     };
                     debug_assert!(b != BlockType::Void);
                     chunk.set_block(x as u32, y as u32, z as u32, b);
@@ -68,36 +119,18 @@ pub fn map_plugin(app: &mut App) {
             }
         }
         chunk
-    }));
+    })
+}
 
-    app.add_plugins(cellular_automata::plugin);
-    app.init_resource::<super::VoxleMaterialHandle>();
-    app.world_mut()
-        .register_component_hooks::<ChunkData>()
-        .on_add(|mut world, ctx| {
-            let blocks = world
-                .get::<ChunkData>(ctx.entity)
-                .expect("Just inserted ChunkData")
-                .iter()
-                .cloned()
-                .collect::<Vec<_>>();
-            let Some(mut chunk) = world.get_mut::<Cells>(ctx.entity) else {
-                let mut chunk = Cells::empty();
-                for (i, block) in blocks.into_iter().enumerate() {
-                    chunk.get_by_index_mut(i).set_block_type(block);
-                }
-                world.commands().entity(ctx.entity).insert(chunk);
-                return;
-            };
-            for (i, block) in blocks.into_iter().enumerate() {
-                chunk.get_by_index_mut(i).set_block_type(block);
-            }
-        });
-    app.add_systems(Update, add_mesh_data_to_loaded_chunks);
-    app.add_systems(
-        PostUpdate,
-        super::remove_evaluation.run_if(crate::voxels::cellular_automata::can_modify_next_step),
-    );
+/// New worlds get the seed picked in the menu, or a random one
+fn seed_new_world(mut commands: Commands, seed: Res<crate::menu::MapSeed>) {
+    let seed = seed.0.unwrap_or_else(|| fastrand::u64(..));
+    info!("New world seed {seed}");
+    commands.insert_resource(cellular_automata::WorldSeed::new(seed));
+}
+
+fn set_generator(mut commands: Commands, seed: Res<cellular_automata::WorldSeed>) {
+    commands.insert_resource(generator(seed.get()));
 }
 
 #[derive(Clone)]
@@ -107,8 +140,9 @@ struct MapNoise {
 }
 
 impl MapNoise {
-    fn new() -> MapNoise {
-        let mut noise = noise::Fbm::new(0);
+    fn new(seed: u64) -> MapNoise {
+        // the noise only takes 32 bits, fold the rest in so no part of the seed is wasted
+        let mut noise = noise::Fbm::new((seed ^ (seed >> 32)) as u32);
         noise.frequency = 0.01;
         noise = noise.set_persistence(0.2);
         MapNoise {
@@ -138,9 +172,9 @@ fn add_mesh_data_to_loaded_chunks(
 ) {
     for (entity, chunk) in &chunks {
         let mut data = ChunkData::new(UVec3::splat(CHUNK_SIZE as u32));
-        for (x, y, z) in BlockIter::new() {
-            let block = chunk.get_cell(x, y, z).get_block_type();
-            data.set_block(x as u32, y as u32, z as u32, block);
+        for (i, cell) in chunk.blocks().enumerate() {
+            let pos = Cells::position(i).as_uvec3();
+            data.set_block(pos.x, pos.y, pos.z, cell.get_block_type());
         }
         commands
             .entity(entity)
@@ -46,10 +46,14 @@ fn spawn_test(
     generator: Res<PhoxelGenerator<BlockType, ChunkId>>,
     matterial_handle: Res<VoxleMaterialHandle>,
     map_size: Res<MapSize>,
+    distance: Res<StreamDistance>,
+    player: Option<Single<&Transform, With<crate::player::Player>>>,
 ) {
     let mut chunk_count = 0;
     let mut total_voxels = 0;
     let map_size = map_size.0;
+    // only whats near the player, the rest streams in as they move
+    let center = player.map(|player| ChunkId::from_translation(player.translation));
     commands
         .spawn((
             Name::new("Chunks"),
@@ -61,10 +65,14 @@ fn spawn_test(
             for x in 0..map_size.x {
                 for z in 0..map_size.z {
                     for y in -y..1 {
+                        let id = ChunkId::new(x as i32, y, z as i32);
+                        if center.is_some_and(|center| !distance.in_load(center, id)) {
+                            continue;
+                        }
                         chunk_count += 1;
                         total_voxels += CHUNK_VOL;
                         root.spawn((
-                            ChunkId::new(x as i32, y, z as i32),
+                            id,
                             // Chunk::<CellData>::empty(),
                             // Mesh3d(Default::default()),
                             generator.clone(),
@@ -74,7 +82,7 @@ fn spawn_test(
                 }
             }
         });
-    println!(
+    info!(
         "Generated: {} chunks\nEquivelent Voxels: {}",
         chunk_count, total_voxels
     );
@@ -100,6 +108,6 @@ fn remove_evaluation(mut chunks: Query<&mut Cells>) {
         }
     }
     if removed > 0 {
-        println!("Removed {} blocks as gas", removed);
+        info!("Removed {} blocks as gas", removed);
     }
 }
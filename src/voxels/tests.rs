@@ -199,3 +199,33 @@ fn fuzz_serde() {
         test!(chunk);
     }
 }
+
+#[test]
+fn neighbours_forget_unloaded_chunks() {
+    use crate::voxels::{ChunkId, ChunkManager, Neighbours};
+    use bevy::prelude::World;
+
+    let mut world = World::new();
+    world.init_resource::<ChunkManager>();
+    world.init_resource::<crate::diagnostics::ChunkCount>();
+    let a = world.spawn(ChunkId::new(0, 0, 0)).id();
+    let b = world.spawn(ChunkId::new(1, 0, 0)).id();
+    assert_eq!(world.get::<Neighbours>(a).unwrap().right(), Some(b));
+    assert_eq!(world.get::<Neighbours>(b).unwrap().left(), Some(a));
+
+    world.despawn(b);
+    assert_eq!(world.get::<Neighbours>(a).unwrap().right(), None);
+    let manager = world.resource::<ChunkManager>();
+    assert_eq!(manager.get_chunk(&ChunkId::new(1, 0, 0)), None);
+
+    let b = world.spawn(ChunkId::new(1, 0, 0)).id();
+    assert_eq!(world.get::<Neighbours>(a).unwrap().right(), Some(b));
+}
+
+#[test]
+fn position_undoes_index() {
+    for (x, y, z) in BlockIter::new() {
+        let pos = Chunk::<BlockType>::position(Chunk::<BlockType>::index(x, y, z));
+        assert_eq!((pos.x, pos.y, pos.z), (x, y, z));
+    }
+}
@@ -0,0 +1,365 @@
+use bevy::{
+    asset::{AssetLoader, AsyncReadExt},
+    prelude::*,
+};
+use chunk_serde::{CompressedChunkData, StrSerializer};
+
+use super::{
+    ChunkManager,
+    text::{AssetError, read_fields},
+};
+use crate::voxels::{
+    BlockType,
+    cellular_automata::{CellData, Cells, PendingEdits},
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_asset::<Blueprint>()
+        .init_asset_loader::<BlueprintLoader>()
+        .init_resource::<Clipboard>();
+    app.add_systems(
+        Update,
+        load_into_clipboard.run_if(|clipboard: Res<Clipboard>| clipboard.loading.is_some()),
+    );
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BlueprintError {
+    #[error("Bad blueprint: {0}")]
+    Asset(#[from] AssetError),
+    #[error("Blueprint is {0} but has {1} cells")]
+    WrongSize(UVec3, usize),
+    #[error("Blueprint is too big: {0}")]
+    TooBig(UVec3),
+    #[error("Blueprints can't be saved on web")]
+    NoFiles,
+}
+
+#[derive(Default)]
+pub struct BlueprintLoader;
+
+impl AssetLoader for BlueprintLoader {
+    type Asset = Blueprint;
+    type Settings = ();
+    type Error = BlueprintError;
+    fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut bevy::asset::LoadContext,
+    ) -> impl bevy::tasks::ConditionalSendFuture<Output = std::result::Result<Self::Asset, Self::Error>>
+    {
+        async move {
+            let mut data = Vec::new();
+            reader
+                .read_to_end(&mut data)
+                .await
+                .map_err(AssetError::from)?;
+            Blueprint::from_text(String::from_utf8(data).map_err(AssetError::from)?)
+        }
+    }
+    fn extensions(&self) -> &[&str] {
+        &["blueprint"]
+    }
+}
+
+/// A box of blocks copied out of the world, can cross any number of chunks
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct Blueprint {
+    size: UVec3,
+    /// x then z then y, same as a chunk
+    blocks: CompressedChunkData<BlockType>,
+    /// only kept if asked for, otherwise pasted blocks keep the tempreture of what they replace
+    tempretures: Option<CompressedChunkData<CellData>>,
+}
+
+impl Blueprint {
+    /// biggest box that can be captured, keeps a typo from copying the whole world
+    pub const MAX_SIZE: u32 = 128;
+
+    /// Copy every block between `a` and `b` inclusive, anything not loaded is `Void`
+    pub fn capture(
+        a: IVec3,
+        b: IVec3,
+        with_tempretures: bool,
+        manager: &ChunkManager,
+        chunks: &Query<&Cells>,
+    ) -> Result<Blueprint, BlueprintError> {
+        let min = a.min(b);
+        let size = (a.max(b) - min + IVec3::ONE).as_uvec3();
+        if size.max_element() > Self::MAX_SIZE {
+            return Err(BlueprintError::TooBig(size));
+        }
+        let mut cells = Vec::with_capacity(volume(size));
+        for y in 0..size.y as i32 {
+            for z in 0..size.z as i32 {
+                for x in 0..size.x as i32 {
+                    let at = min + IVec3::new(x, y, z);
+                    let cell = manager
+                        .get_chunk_and_local_block(at.x, at.y, at.z)
+                        .and_then(|(entity, local)| {
+                            let cells = chunks.get(entity).ok()?;
+                            Some(cells.get_cell(local.x, local.y, local.z))
+                        })
+                        .unwrap_or(CellData {
+                            block: BlockType::Void,
+                            ..Default::default()
+                        });
+                    cells.push(cell);
+                }
+            }
+        }
+        let blocks = cells.iter().map(|c| c.block).collect::<Vec<_>>();
+        let tempretures = with_tempretures.then(|| {
+            // only the tempreture gets used so don't let the block split runs
+            let cells = cells
+                .iter()
+                .map(|c| CellData::at_k(BlockType::Air, c.tempreture))
+                .collect::<Vec<_>>();
+            compress(&cells)
+        });
+        Ok(Blueprint {
+            size,
+            blocks: compress(&blocks),
+            tempretures,
+        })
+    }
+
+    pub fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    /// size once pasted with `turns`
+    pub fn turned_size(&self, turns: u8) -> UVec3 {
+        if turns % 2 == 1 {
+            UVec3::new(self.size.z, self.size.y, self.size.x)
+        } else {
+            self.size
+        }
+    }
+
+    /// Queue every block that isn't `Void` to be set with its min corner at `origin`
+    /// `mirror` flips x before turning `turns` quarter turns about y
+    /// returns how many blocks landed outside the loaded world and were dropped
+    pub fn paste(
+        &self,
+        origin: IVec3,
+        turns: u8,
+        mirror: bool,
+        manager: &ChunkManager,
+        edits: &mut PendingEdits,
+    ) -> usize {
+        let blocks = expand(&self.blocks, volume(self.size));
+        let tempretures = self
+            .tempretures
+            .as_ref()
+            .map(|t| expand(t, volume(self.size)));
+        let mut dropped = 0;
+        let mut i = 0;
+        edits.new_action();
+        for y in 0..self.size.y as i32 {
+            for z in 0..self.size.z as i32 {
+                for x in 0..self.size.x as i32 {
+                    let block = blocks[i];
+                    let k = tempretures.as_ref().map(|t| t[i].tempreture);
+                    i += 1;
+                    if block == BlockType::Void {
+                        continue;
+                    }
+                    let at = origin + transform(IVec3::new(x, y, z), self.size, turns, mirror);
+                    if manager
+                        .get_chunk_and_local_block(at.x, at.y, at.z)
+                        .is_none()
+                    {
+                        dropped += 1;
+                        continue;
+                    }
+                    match k {
+                        Some(k) => edits.push_at_k(at, block, k),
+                        None => edits.push(at, block),
+                    }
+                }
+            }
+        }
+        dropped
+    }
+
+    pub fn to_text(&self) -> Result<String, BlueprintError> {
+        let mut serde = StrSerializer::new();
+        serde.push_str(&format!(
+            "size: ({}, {}, {})\nblocks: ",
+            self.size.x, self.size.y, self.size.z
+        ));
+        serde.insert(&self.blocks).map_err(AssetError::Parse)?;
+        if let Some(tempretures) = &self.tempretures {
+            serde.push_str("\ntempretures: ");
+            serde.insert(tempretures).map_err(AssetError::Parse)?;
+        }
+        serde.push('\n');
+        Ok(serde.finalize())
+    }
+
+    pub fn from_text(data: String) -> Result<Blueprint, BlueprintError> {
+        let mut size = None;
+        let mut blocks = None;
+        let mut tempretures = None;
+        read_fields(data, |field, serde| {
+            match field {
+                "size" => size = Some(extract_size(serde)?),
+                "blocks" => blocks = Some(serde.extract()?),
+                "tempretures" => tempretures = Some(serde.extract()?),
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        let size = size.ok_or(AssetError::MissingField("size"))?;
+        if size.max_element() > Self::MAX_SIZE {
+            return Err(BlueprintError::TooBig(size));
+        }
+        let blocks = blocks.ok_or(AssetError::MissingField("blocks"))?;
+        check_size(&blocks, size)?;
+        if let Some(tempretures) = &tempretures {
+            check_size(tempretures, size)?;
+        }
+        Ok(Blueprint {
+            size,
+            blocks,
+            tempretures,
+        })
+    }
+}
+
+fn volume(size: UVec3) -> usize {
+    size.x as usize * size.y as usize * size.z as usize
+}
+
+fn check_size<T>(cells: &CompressedChunkData<T>, size: UVec3) -> Result<(), BlueprintError> {
+    match cells.cell_count() {
+        Some(count) if count != volume(size) => Err(BlueprintError::WrongSize(size, count)),
+        _ => Ok(()),
+    }
+}
+
+fn extract_size(serde: &mut StrSerializer) -> bevy::ecs::error::Result<UVec3> {
+    serde.expect('(')?;
+    let x = serde.word()?.parse()?;
+    serde.expect(',')?;
+    let y = serde.word()?.parse()?;
+    serde.expect(',')?;
+    let z = serde.word()?.parse()?;
+    serde.expect(')')?;
+    Ok(UVec3::new(x, y, z))
+}
+
+/// Where `local` ends up in a box of `size` after mirroring and turning
+fn transform(local: IVec3, size: UVec3, turns: u8, mirror: bool) -> IVec3 {
+    let mut size = size.as_ivec3();
+    let mut out = local;
+    if mirror {
+        out.x = size.x - 1 - out.x;
+    }
+    for _ in 0..turns % 4 {
+        out = IVec3::new(size.z - 1 - out.z, out.y, out.x);
+        size = IVec3::new(size.z, size.y, size.x);
+    }
+    out
+}
+
+/// Run length encode any number of cells, runs longer than a u16 get split
+fn compress<T: Copy + Eq>(cells: &[T]) -> CompressedChunkData<T> {
+    let mut runs: Vec<(T, u16)> = Vec::new();
+    for cell in cells {
+        match runs.last_mut() {
+            Some((last, len)) if last == cell && *len < u16::MAX => *len += 1,
+            _ => runs.push((*cell, 1)),
+        }
+    }
+    match runs[..] {
+        [(only, _)] => CompressedChunkData::Solid(only),
+        _ => CompressedChunkData::RunLen(runs),
+    }
+}
+
+fn expand<T: Copy + Default>(data: &CompressedChunkData<T>, len: usize) -> Vec<T> {
+    match data {
+        CompressedChunkData::Solid(v) => vec![*v; len],
+        CompressedChunkData::RunLen(runs) => runs
+            .iter()
+            .flat_map(|(v, n)| std::iter::repeat_n(*v, *n as usize))
+            .collect(),
+        CompressedChunkData::Raw(cells) => cells.clone(),
+        CompressedChunkData::Error(_) => vec![T::default(); len],
+    }
+}
+
+/// What `blueprint paste` puts down
+#[derive(Resource, Default)]
+pub struct Clipboard {
+    pub blueprint: Option<Blueprint>,
+    loading: Option<Handle<Blueprint>>,
+}
+
+impl Clipboard {
+    /// swap the clipboard for a blueprint asset once it loads
+    pub fn load(&mut self, handle: Handle<Blueprint>) {
+        self.loading = Some(handle);
+    }
+}
+
+fn load_into_clipboard(
+    mut clipboard: ResMut<Clipboard>,
+    blueprints: Res<Assets<Blueprint>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(handle) = &clipboard.loading else {
+        return;
+    };
+    if let Some(blueprint) = blueprints.get(handle) {
+        info!("loaded blueprint {:?} into clipboard", handle.path());
+        clipboard.blueprint = Some(blueprint.clone());
+        clipboard.loading = None;
+    } else if asset_server.load_state(handle).is_failed() {
+        error!("Failed to load blueprint {:?}", handle.path());
+        clipboard.loading = None;
+    }
+}
+
+#[test]
+fn blueprint_round_trips_and_turns() {
+    let blueprint = Blueprint {
+        size: UVec3::new(3, 1, 2),
+        blocks: compress(&[
+            BlockType::Copper,
+            BlockType::Copper,
+            BlockType::Uranium,
+            BlockType::Void,
+            BlockType::Void,
+            BlockType::Water,
+        ]),
+        tempretures: None,
+    };
+    let text = Blueprint::from_text(blueprint.to_text().unwrap()).unwrap();
+    assert_eq!(text.size, blueprint.size);
+    assert!(text.blocks == blueprint.blocks);
+    assert_eq!(expand(&text.blocks, 6)[5], BlockType::Water);
+    assert!(matches!(
+        Blueprint::from_text("size: (1, 1, 1)\nshape: x".to_string()),
+        Err(BlueprintError::Asset(AssetError::UnknownField(_)))
+    ));
+
+    let size = blueprint.size;
+    assert_eq!(transform(IVec3::ZERO, size, 0, true), IVec3::new(2, 0, 0));
+    // a quarter turn puts the old z along x
+    assert_eq!(transform(IVec3::ZERO, size, 1, false), IVec3::new(1, 0, 0));
+    assert_eq!(
+        transform(IVec3::new(2, 0, 1), size, 1, false),
+        IVec3::new(0, 0, 2)
+    );
+    for turns in 0..4 {
+        let turned = blueprint.turned_size(turns).as_ivec3();
+        for (x, z) in [(0, 0), (2, 0), (0, 1), (2, 1)] {
+            let at = transform(IVec3::new(x, 0, z), size, turns, false);
+            assert!(at.cmpge(IVec3::ZERO).all() && at.cmplt(turned).all());
+        }
+    }
+}
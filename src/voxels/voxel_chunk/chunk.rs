@@ -1,16 +1,25 @@
 use bevy::prelude::*;
-use chunk_serde::CompressedChunkData;
+use chunk_serde::{CompressedChunkData, Palette};
+use strum::IntoEnumIterator;
 
 use crate::voxels::{
     block::BlockType,
-    cellular_automata::{CellData, CellId, Cells, NextStep, TargetTick, VoxelStep, VoxelTick},
+    cellular_automata::{
+        CellData, CellId, Cells, NextStep, TargetTick, VoxelStep, VoxelTick, WorldSeed,
+    },
     map::{CHUNK_AREA, CHUNK_SIZE, CHUNK_VOL, ChunkData},
-    voxel_chunk::ChunkId,
+    voxel_chunk::{
+        ChunkId,
+        save::{self, LoadReport},
+        text,
+    },
 };
 
 #[derive(Default, Resource)]
 pub struct ChunkManager {
     map: indexmap::IndexMap<ChunkId, Entity>,
+    // chunks that have been streamed out, packed the same way as a save
+    unloaded: indexmap::IndexMap<ChunkId, CompressedChunkData<save::SavedCell>>,
     // the lowest x,y,z of any chunk
     lowest: ChunkId,
     // the highest x,y,z of any chunk
@@ -21,8 +30,43 @@ impl ChunkManager {
     pub fn insert_chunk(&mut self, id: ChunkId, entity: Entity) -> Option<Entity> {
         self.lowest = self.lowest.min(id);
         self.higes = self.higes.max(id);
+        // it's back in the world so the packed copy is out of date
+        self.unloaded.swap_remove(&id);
         self.map.insert(id, entity)
     }
+
+    /// Pack a chunk away before it's despawned, it's still saved with the world
+    /// and comes back with `get_unloaded`
+    pub fn unload(&mut self, id: ChunkId, cells: &Cells) {
+        self.unloaded.insert(id, save::save_cells(cells));
+    }
+
+    /// The cells of a chunk that was unloaded, it stays packed till the chunk is inserted again
+    pub fn get_unloaded(&self, id: &ChunkId) -> Option<Cells> {
+        // packed with every block so there's no id it can't load
+        let blocks = BlockType::iter().collect::<Vec<_>>();
+        self.unloaded
+            .get(id)
+            .and_then(|saved| save::load_cells(saved, &blocks).ok())
+    }
+
+    pub fn unloaded_len(&self) -> usize {
+        self.unloaded.len()
+    }
+
+    /// Forget every unloaded chunk, for when a whole new world is loaded
+    pub fn clear_unloaded(&mut self) {
+        self.unloaded.clear();
+    }
+
+    // unloaded chunks that haven't been despawned yet are still in `map`
+    fn unloaded_only(
+        &self,
+    ) -> impl Iterator<Item = (&ChunkId, &CompressedChunkData<save::SavedCell>)> {
+        self.unloaded
+            .iter()
+            .filter(|(id, _)| !self.map.contains_key(*id))
+    }
     pub fn remove_chunk(&mut self, id: &ChunkId) -> Option<Entity> {
         self.map.swap_remove(id)
     }
@@ -44,33 +88,50 @@ impl ChunkManager {
         serde
             .insert(b"PhoxC")
             .map_err(ChunkManagerError::SerdeError)?;
-        let compressed = cells.compress();
-        serde
-            .insert(&compressed)
+        save::insert_version(&mut serde, &save::CHUNK_MIGRATIONS)
+            .map_err(ChunkManagerError::SerdeError)?;
+        let mut body = chunk_serde::BinSerializer::new();
+        body.insert(&save::block_palette())
+            .map_err(ChunkManagerError::SerdeError)?;
+        body.insert(&save::save_cells(cells))
             .map_err(ChunkManagerError::SerdeError)?;
+        save::append_checksum(&mut body).map_err(ChunkManagerError::SerdeError)?;
+        serde.push_slice(body.as_ref());
         Ok(serde.finalize())
     }
 
+    /// Blocks are saved by palette index, with the palette up front
+    /// so the `BlockType` list can change without breaking old saves.
+    /// Every chunk has its own checksum so one bad chunk doesn't lose the whole world
     pub fn save_world(
         &self,
         data: &Query<&Cells>,
         tick: u64,
+        seed: u64,
     ) -> Result<Vec<u8>, ChunkManagerError> {
         let mut serde = chunk_serde::BinSerializer::new();
         serde
             .insert(b"PhoxW")
             .map_err(ChunkManagerError::SerdeError)?;
-        serde.insert(&tick).map_err(ChunkManagerError::SerdeError)?;
-        let len = self.len() as u64;
-        serde.insert(&len).map_err(ChunkManagerError::SerdeError)?;
+        save::insert_version(&mut serde, &save::WORLD_MIGRATIONS)
+            .map_err(ChunkManagerError::SerdeError)?;
+        let mut body = chunk_serde::BinSerializer::new();
+        body.insert(&tick).map_err(ChunkManagerError::SerdeError)?;
+        body.insert(&seed).map_err(ChunkManagerError::SerdeError)?;
+        body.insert(&save::block_palette())
+            .map_err(ChunkManagerError::SerdeError)?;
+        let len = (self.len() + self.unloaded_only().count()) as u64;
+        body.insert(&len).map_err(ChunkManagerError::SerdeError)?;
         for (id, entity) in self.map.iter() {
             let cells = data.get(*entity)?;
-            let compressed = cells.compress();
-            serde.insert(id).map_err(ChunkManagerError::SerdeError)?;
-            serde
-                .insert(&compressed)
+            save::insert_chunk(&mut body, id, &save::save_cells(cells))
                 .map_err(ChunkManagerError::SerdeError)?;
         }
+        for (id, cells) in self.unloaded_only() {
+            save::insert_chunk(&mut body, id, cells).map_err(ChunkManagerError::SerdeError)?;
+        }
+        save::append_checksum(&mut body).map_err(ChunkManagerError::SerdeError)?;
+        serde.push_slice(body.as_ref());
         Ok(serde.finalize())
     }
 
@@ -89,10 +150,28 @@ impl ChunkManager {
                 bevy::ecs::error::BevyError::from("Attempted to load world as chunk data"),
             ));
         }
+        let (version, body) =
+            save::split_version(&data[5..]).map_err(ChunkManagerError::SerdeError)?;
+        let body = save::CHUNK_MIGRATIONS
+            .upgrade(version, body)
+            .map_err(ChunkManagerError::SerdeError)?;
+        let (body, intact) = save::split_checksum(&body).map_err(ChunkManagerError::SerdeError)?;
+        if !intact {
+            return Err(ChunkManagerError::SerdeError(
+                chunk_serde::BinError::ChecksumMismatch.into(),
+            ));
+        }
+        let mut serde = chunk_serde::BinDeSerializer::new(body);
+        let blocks = serde
+            .extract::<Palette>()
+            .and_then(|palette| save::palette_blocks(&palette))
+            .map_err(ChunkManagerError::SerdeError)?;
         let compressed = serde
-            .extract::<CompressedChunkData<CellData>>()
+            .extract::<CompressedChunkData<save::SavedCell>>()
             .map_err(ChunkManagerError::SerdeError)?;
-        let cells = Cells::decompress(&compressed);
+        save::check_cell_count(&compressed).map_err(ChunkManagerError::SerdeError)?;
+        let cells = save::load_cells(&compressed, &blocks)
+            .map_err(|e| ChunkManagerError::SerdeError(e.into()))?;
         if let Some(entity) = self.get_chunk(&id) {
             commands.entity(entity).remove::<NextStep>().insert(cells);
         } else {
@@ -101,34 +180,70 @@ impl ChunkManager {
         Ok(())
     }
 
+    /// Chunks that fail there checksum are left as they are and listed in the report,
+    /// only a broken header fails the whole load
     pub fn load_world(
         &self,
         data: &[u8],
         commands: &mut Commands,
-    ) -> Result<(), ChunkManagerError> {
+    ) -> Result<LoadReport, ChunkManagerError> {
         let mut serde = chunk_serde::BinDeSerializer::new(data);
         let magic = serde
             .extract::<[u8; 5]>()
-            .map_err(ChunkManagerError::SerdeError)
-            .unwrap();
+            .map_err(ChunkManagerError::SerdeError)?;
         if magic != *b"PhoxW" {
             return Err(ChunkManagerError::SerdeError(
                 bevy::ecs::error::BevyError::from("Attempted to load chunk data as world data"),
             ));
         }
+        let (version, body) =
+            save::split_version(&data[5..]).map_err(ChunkManagerError::SerdeError)?;
+        let body = save::WORLD_MIGRATIONS
+            .upgrade(version, body)
+            .map_err(ChunkManagerError::SerdeError)?;
+        let (body, intact) = save::split_checksum(&body).map_err(ChunkManagerError::SerdeError)?;
+        let mut report = LoadReport {
+            intact,
+            ..Default::default()
+        };
+        // anything streamed out belongs to the old world
+        commands.queue(|world: &mut World| world.resource_mut::<ChunkManager>().clear_unloaded());
+        let mut serde = chunk_serde::BinDeSerializer::new(body);
         let tick = serde
             .extract::<u64>()
             .map_err(ChunkManagerError::SerdeError)?;
+        let seed = serde
+            .extract::<u64>()
+            .map_err(ChunkManagerError::SerdeError)?;
+        let blocks = serde
+            .extract::<Palette>()
+            .and_then(|palette| save::palette_blocks(&palette))
+            .map_err(ChunkManagerError::SerdeError)?;
         let len = serde
             .extract::<u64>()
             .map_err(ChunkManagerError::SerdeError)?;
 
-        for _ in 0..len {
-            let id = serde.extract::<ChunkId>().unwrap();
-            let compressed = serde
-                .extract::<CompressedChunkData<CellData>>()
-                .map_err(ChunkManagerError::SerdeError)?;
-            let cells = Cells::decompress(&compressed);
+        for i in 0..len {
+            let (id, compressed) = match save::take_chunk(&mut serde) {
+                Ok(Ok(chunk)) => chunk,
+                Ok(Err(e)) => {
+                    report.corrupted.push((i, e));
+                    continue;
+                }
+                Err(e) => {
+                    report.corrupted.push((i, e.into()));
+                    report.skipped = len - i - 1;
+                    break;
+                }
+            };
+            let cells = match save::load_cells(&compressed, &blocks) {
+                Ok(cells) => cells,
+                Err(e) => {
+                    report.corrupted.push((i, e.in_chunk(id)));
+                    continue;
+                }
+            };
+            report.loaded += 1;
 
             if let Some(entity) = self.get_chunk(&id) {
                 commands
@@ -139,10 +254,14 @@ impl ChunkManager {
                 commands.spawn((cells, id));
             }
         }
+        commands.insert_resource(WorldSeed::new(seed));
         commands.insert_resource(VoxelTick::new(tick));
         commands.insert_resource(TargetTick::new(tick));
         commands.insert_resource(VoxelStep::default());
-        Ok(())
+        if !report.is_clean() {
+            warn!("damaged world save: {report}");
+        }
+        Ok(report)
     }
 
     pub fn save_compressed_chunk(
@@ -214,6 +333,7 @@ impl ChunkManager {
         let compressed = serde
             .extract::<CompressedChunkData<BlockType>>()
             .map_err(ChunkManagerError::SerdeError)?;
+        save::check_cell_count(&compressed).map_err(ChunkManagerError::SerdeError)?;
         let chunk_data = Chunk::<BlockType>::decompress(&compressed);
         let mut chunk = Chunk::empty();
         for (i, b) in chunk_data.blocks.iter().enumerate() {
@@ -237,19 +357,19 @@ impl ChunkManager {
         let mut serde = chunk_serde::BinDeSerializer::new(data);
         let magic = serde
             .extract::<[u8; 5]>()
-            .map_err(ChunkManagerError::SerdeError)
-            .unwrap();
+            .map_err(ChunkManagerError::SerdeError)?;
         if magic != *b"PhoxM" {
-            panic!("Attempted to load chunk data as world data");
+            return Err(ChunkManagerError::SerdeError(
+                bevy::ecs::error::BevyError::from("Attempted to load chunk data as world data"),
+            ));
         }
         let tick = serde
             .extract::<u64>()
-            .map_err(ChunkManagerError::SerdeError)
-            .unwrap();
+            .map_err(ChunkManagerError::SerdeError)?;
         let len = serde
             .extract::<u64>()
-            .map_err(ChunkManagerError::SerdeError)
-            .unwrap();
+            .map_err(ChunkManagerError::SerdeError)?;
+        commands.queue(|world: &mut World| world.resource_mut::<ChunkManager>().clear_unloaded());
 
         for _ in 0..len {
             let id = serde
@@ -258,6 +378,7 @@ impl ChunkManager {
             let compressed = serde
                 .extract::<CompressedChunkData<BlockType>>()
                 .map_err(ChunkManagerError::SerdeError)?;
+            save::check_cell_count(&compressed).map_err(ChunkManagerError::SerdeError)?;
             let chunk_data = Chunk::<BlockType>::decompress(&compressed);
             let mut chunk = Chunk::empty();
             for (i, b) in chunk_data.blocks.iter().enumerate() {
@@ -280,6 +401,81 @@ impl ChunkManager {
         Ok(())
     }
 
+    /// A chunk as text, see `save_text_world`
+    pub fn save_text_chunk(
+        &self,
+        chunk: ChunkId,
+        data: &Query<&Cells>,
+    ) -> Result<String, ChunkManagerError> {
+        let Some(entity) = self.get_chunk(&chunk) else {
+            return Err(ChunkManagerError::NoEntity(chunk));
+        };
+        let mut serde = chunk_serde::StrSerializer::new();
+        text::insert_header(&mut serde).map_err(ChunkManagerError::SerdeError)?;
+        text::insert_chunk(&mut serde, &chunk, data.get(entity)?)
+            .map_err(ChunkManagerError::SerdeError)?;
+        Ok(serde.finalize())
+    }
+
+    /// Block names, tempretures and phase of every cell, run length grouped.
+    /// Made for reading and diffing, it only keeps tempreture so use `save_world` to keep everything
+    pub fn save_text_world(
+        &self,
+        data: &Query<&Cells>,
+        tick: u64,
+        seed: u64,
+    ) -> Result<String, ChunkManagerError> {
+        let mut serde = chunk_serde::StrSerializer::new();
+        text::insert_header(&mut serde).map_err(ChunkManagerError::SerdeError)?;
+        text::insert_world(&mut serde, tick, seed).map_err(ChunkManagerError::SerdeError)?;
+        for (id, entity) in self.map.iter() {
+            text::insert_chunk(&mut serde, id, data.get(*entity)?)
+                .map_err(ChunkManagerError::SerdeError)?;
+        }
+        let blocks = BlockType::iter().collect::<Vec<_>>();
+        for (id, cells) in self.unloaded_only() {
+            let cells = save::load_cells(cells, &blocks)
+                .map_err(|e| ChunkManagerError::SerdeError(e.into()))?;
+            text::insert_chunk(&mut serde, id, &cells).map_err(ChunkManagerError::SerdeError)?;
+        }
+        Ok(serde.finalize())
+    }
+
+    /// Load every chunk in a text export where it was,
+    /// a whole world also sets the tick and seed.
+    /// Returns the number of chunks loaded
+    pub fn load_text(
+        &self,
+        data: String,
+        commands: &mut Commands,
+    ) -> Result<usize, ChunkManagerError> {
+        let world = text::parse(data).map_err(ChunkManagerError::SerdeError)?;
+        let len = world.chunks.len();
+        if world.tick.is_some() {
+            commands
+                .queue(|world: &mut World| world.resource_mut::<ChunkManager>().clear_unloaded());
+        }
+        for (id, cells) in world.chunks {
+            if let Some(entity) = self.get_chunk(&id) {
+                commands
+                    .entity(entity)
+                    .remove::<(NextStep, ChunkData)>()
+                    .insert(cells);
+            } else {
+                commands.spawn((cells, id));
+            }
+        }
+        if let Some(seed) = world.seed {
+            commands.insert_resource(WorldSeed::new(seed));
+        }
+        if let Some(tick) = world.tick {
+            commands.insert_resource(VoxelTick::new(tick));
+            commands.insert_resource(TargetTick::new(tick));
+            commands.insert_resource(VoxelStep::default());
+        }
+        Ok(len)
+    }
+
     pub fn len(&self) -> usize {
         self.map.len()
     }
@@ -374,6 +570,17 @@ impl<T> Chunk<T> {
         (x + z * CHUNK_SIZE + y * CHUNK_AREA) as usize
     }
 
+    /// The reverse of `index`
+    #[inline(always)]
+    pub fn position(index: usize) -> IVec3 {
+        let index = index as i32;
+        IVec3::new(
+            index % CHUNK_SIZE,
+            index / CHUNK_AREA,
+            (index / CHUNK_SIZE) % CHUNK_SIZE,
+        )
+    }
+
     #[inline(always)]
     fn in_bounds(x: i32, y: i32, z: i32) -> bool {
         x < CHUNK_SIZE && y < CHUNK_SIZE && z < CHUNK_SIZE && x >= 0 && y >= 0 && z >= 0
@@ -91,14 +91,7 @@ impl ChunkId {
 
         for (other, direction) in recip {
             if let Some(mut neighbours) = world.get_mut::<Neighbours>(other) {
-                match direction {
-                    NeighbourDirection::Up => neighbours.up = Some(ctx.entity),
-                    NeighbourDirection::Down => neighbours.down = Some(ctx.entity),
-                    NeighbourDirection::Left => neighbours.left = Some(ctx.entity),
-                    NeighbourDirection::Right => neighbours.right = Some(ctx.entity),
-                    NeighbourDirection::Front => neighbours.front = Some(ctx.entity),
-                    NeighbourDirection::Back => neighbours.back = Some(ctx.entity),
-                }
+                *neighbours.slot(direction) = Some(ctx.entity);
             } else {
                 warn!("Failed to get Neighbours for {other:?} this is probably a bug");
             }
@@ -140,6 +133,22 @@ impl ChunkId {
                 map.insert_chunk(id, old);
             }
         }
+
+        // chunks can leave while the rest of the world keeps going
+        // so don't leave anything pointing at this one
+        let Some(mut neighbours) = world.get_mut::<Neighbours>(ctx.entity) else {
+            return;
+        };
+        let others = neighbours.iter().collect::<Vec<_>>();
+        *neighbours = Neighbours::default();
+        for (direction, other) in others {
+            if let Some(mut neighbours) = world.get_mut::<Neighbours>(other) {
+                let slot = neighbours.slot(direction.rev());
+                if *slot == Some(ctx.entity) {
+                    *slot = None;
+                }
+            }
+        }
     }
 
     pub fn from_translation(mut translation: Vec3) -> Self {
@@ -151,22 +160,20 @@ impl ChunkId {
         (self.0 * CHUNK_SIZE).as_vec3()
     }
 
-    pub fn from_str(str: &str) -> Result<Self, &'static str> {
+    pub fn from_str(str: &str) -> Result<Self, ParseChunkIdError> {
         let mut s = str.trim();
         if s.is_empty() {
-            return Err("ChunkId cannot be empty");
+            return Err(ParseChunkIdError::Empty);
         }
         if s.contains('(') && s.contains(')') {
             let mut iter = s.split('(');
-            let _ = iter
-                .next()
-                .ok_or("Failed to get Preceding ( with trailing )")?;
+            let _ = iter.next().ok_or(ParseChunkIdError::Brackets)?;
             s = iter
                 .next()
-                .ok_or("Failed to find value after (")?
+                .ok_or(ParseChunkIdError::Brackets)?
                 .split(')')
                 .next()
-                .ok_or("Failed to find trailing )")?;
+                .ok_or(ParseChunkIdError::Brackets)?;
         }
         #[allow(unused_assignments)]
         let mut x = None;
@@ -204,29 +211,41 @@ impl ChunkId {
             z = split.next();
         };
 
-        let x = x.ok_or("Failed to find x value")?.trim();
-        let y = y.ok_or("Failed to find y value")?.trim();
-        let z = z.ok_or("Failed to find z value")?.trim();
+        let x = x.ok_or(ParseChunkIdError::Missing('x'))?.trim();
+        let y = y.ok_or(ParseChunkIdError::Missing('y'))?.trim();
+        let z = z.ok_or(ParseChunkIdError::Missing('z'))?.trim();
 
         let x = x
             .trim_start_matches(|c: char| !c.is_numeric() && c != '-')
             .trim()
             .parse::<i32>()
-            .map_err(|_| "Failed to parse x")?;
+            .map_err(|e| ParseChunkIdError::Number('x', e))?;
         let y = y
             .trim_start_matches(|c: char| !c.is_numeric() && c != '-')
             .trim()
             .parse::<i32>()
-            .map_err(|_| "Failed to parse y")?;
+            .map_err(|e| ParseChunkIdError::Number('y', e))?;
         let z = z
             .trim_start_matches(|c: char| !c.is_numeric() && c != '-')
             .trim()
             .parse::<i32>()
-            .map_err(|_| "Failed to parse z")?;
+            .map_err(|e| ParseChunkIdError::Number('z', e))?;
         Ok(ChunkId(IVec3::new(x, y, z)))
     }
 }
 
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ParseChunkIdError {
+    #[error("ChunkId cannot be empty")]
+    Empty,
+    #[error("Brackets around a ChunkId must be matched")]
+    Brackets,
+    #[error("Failed to find {0} value")]
+    Missing(char),
+    #[error("Failed to parse {0}: {1}")]
+    Number(char, std::num::ParseIntError),
+}
+
 #[derive(Component, Debug, Default)]
 pub struct Neighbours {
     up: Option<Entity>,
@@ -392,6 +411,17 @@ impl Neighbours {
         self.back
     }
 
+    fn slot(&mut self, direction: NeighbourDirection) -> &mut Option<Entity> {
+        match direction {
+            NeighbourDirection::Up => &mut self.up,
+            NeighbourDirection::Down => &mut self.down,
+            NeighbourDirection::Left => &mut self.left,
+            NeighbourDirection::Right => &mut self.right,
+            NeighbourDirection::Front => &mut self.front,
+            NeighbourDirection::Back => &mut self.back,
+        }
+    }
+
     pub fn iter(&self) -> NeighboursIter {
         NeighboursIter {
             neighbours: self,
@@ -449,6 +479,27 @@ impl chunk_serde::Serialize for ChunkId {
         let z = i32::from_be_bytes(slice[8..12].try_into().unwrap());
         Ok((ChunkId(IVec3::new(x, y, z)), 12))
     }
+
+    /// `(x, y, z)`
+    fn insert_str(&self, serializer: &mut chunk_serde::StrSerializer) -> Result<usize> {
+        let start = serializer.len();
+        serializer.write(format_args!("({}, {}, {})", self.x, self.y, self.z))?;
+        Ok(serializer.len() - start)
+    }
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        let mut used = chunk_serde::expect_char(str, '(')?;
+        let mut xyz = [0; 3];
+        for (i, v) in xyz.iter_mut().enumerate() {
+            if i > 0 {
+                used += chunk_serde::expect_char(&str[used..], ',')?;
+            }
+            let (value, len) = <i32 as chunk_serde::Serialize>::extract_str(&str[used..])?;
+            *v = value;
+            used += len;
+        }
+        used += chunk_serde::expect_char(&str[used..], ')')?;
+        Ok((ChunkId(IVec3::from_array(xyz)), used))
+    }
 }
 
 #[test]
@@ -1,7 +1,14 @@
+pub mod blueprint;
 pub mod chunk;
 mod id;
 pub mod prefab;
+mod save;
+pub mod stream;
+mod text;
 
-pub use id::{ChunkId, NeighbourDirection, Neighbours, VoidNeighbours};
+pub use id::{ChunkId, NeighbourDirection, Neighbours, ParseChunkIdError, VoidNeighbours};
 
 pub use chunk::{Chunk, ChunkManager};
+pub use save::{LoadReport, block_palette, palette_blocks};
+pub use stream::StreamDistance;
+pub use text::AssetError;
@@ -2,9 +2,43 @@ use bevy::{
     asset::{AssetLoader, AsyncReadExt},
     prelude::*,
 };
-use chunk_serde::{BinSerializer, CompressedChunkData};
+use chunk_serde::{
+    BinDeSerializer, BinSerializer, CompressedChunkData, Migration, Migrations, Palette,
+    StrSerializer,
+};
+
+use super::{
+    Chunk,
+    save::{
+        SavedCell, block_palette, check_cell_count, insert_version, map_compressed, palette_blocks,
+        split_version,
+    },
+    text::{AssetError, read_fields},
+};
+use crate::{
+    MeltdownError,
+    voxels::{
+        BlockType,
+        cellular_automata::{ApplyStep, CellData, PendingEdits},
+        map::CHUNK_SIZE,
+    },
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_asset::<ChunkPrefab>()
+        .init_asset_loader::<ChunkPrefabLoader>()
+        .init_resource::<PendingStamps>();
+    app.add_systems(
+        Update,
+        stamp_prefabs
+            .run_if(|stamps: Res<PendingStamps>| !stamps.0.is_empty())
+            .before(ApplyStep::Edit),
+    );
+}
 
-use crate::voxels::{BlockType, cellular_automata::CellData};
+#[derive(Debug, thiserror::Error)]
+#[error("Bad prefab: {0}")]
+pub struct PrefabError(#[from] AssetError);
 
 #[derive(Default)]
 pub struct ChunkPrefabLoader;
@@ -12,48 +46,244 @@ pub struct ChunkPrefabLoader;
 impl AssetLoader for ChunkPrefabLoader {
     type Asset = ChunkPrefab;
     type Settings = ();
-    type Error = &'static str;
+    type Error = PrefabError;
     fn load(
         &self,
         reader: &mut dyn bevy::asset::io::Reader,
-        settings: &Self::Settings,
-        load_context: &mut bevy::asset::LoadContext,
+        _settings: &Self::Settings,
+        _load_context: &mut bevy::asset::LoadContext,
     ) -> impl bevy::tasks::ConditionalSendFuture<Output = std::result::Result<Self::Asset, Self::Error>>
     {
         async move {
-            let mut data = String::new();
+            let mut data = Vec::new();
             reader
-                .read_to_string(&mut data)
+                .read_to_end(&mut data)
                 .await
-                .or(Err("Failed to read to str"))?;
-            from_str(data).await
+                .map_err(AssetError::from)?;
+            if data.starts_with(ChunkPrefab::MAGIC) {
+                from_bytes(&data)
+            } else {
+                from_str(String::from_utf8(data).map_err(AssetError::from)?)
+            }
         }
     }
     fn extensions(&self) -> &[&str] {
         &["phoxel"]
     }
 }
-#[derive(Asset, TypePath)]
+
+/// A chunk worth of blocks to stamp into the world
+/// `chunk` is the shape, anything `Void` leaves the world alone
+/// `automita` is the state each cell starts in, only its tempreture is used
+#[derive(Asset, TypePath, Debug)]
 pub struct ChunkPrefab {
     chunk: CompressedChunkData<BlockType>,
     automita: CompressedChunkData<CellData>,
 }
 
-async fn from_str(data: String) -> Result<ChunkPrefab, &'static str> {
-    todo!()
+/// upgrades for binary `PhoxP` prefabs
+pub const PREFAB_MIGRATIONS: Migrations = Migrations::new(&[prefab_v0_to_v1 as Migration]);
+
+impl ChunkPrefab {
+    const MAGIC: &[u8] = b"PhoxP";
+
+    pub fn new(
+        chunk: CompressedChunkData<BlockType>,
+        automita: CompressedChunkData<CellData>,
+    ) -> Result<ChunkPrefab, PrefabError> {
+        check_cell_count(&chunk).map_err(AssetError::Parse)?;
+        check_cell_count(&automita).map_err(AssetError::Parse)?;
+        Ok(ChunkPrefab { chunk, automita })
+    }
+
+    /// binary encoding, `PhoxP`, the version and block palette, then the chunk and automita
+    /// as `SavedCell`s like a world save
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PrefabError> {
+        let chunk = map_compressed(&self.chunk, |_, block| Ok(SavedCell::of_block(block)))
+            .map_err(|e| AssetError::Parse(e.into()))?;
+        let automita = map_compressed(&self.automita, |_, cell| Ok(SavedCell::new(cell)))
+            .map_err(|e| AssetError::Parse(e.into()))?;
+        let mut serde = BinSerializer::new();
+        serde.push_slice(Self::MAGIC);
+        insert_version(&mut serde, &PREFAB_MIGRATIONS).map_err(AssetError::Parse)?;
+        serde.insert(&block_palette()).map_err(AssetError::Parse)?;
+        serde.insert(&chunk).map_err(AssetError::Parse)?;
+        serde.insert(&automita).map_err(AssetError::Parse)?;
+        Ok(serde.finalize())
+    }
+
+    /// text encoding, `chunk: ...` and `automita: ...`
+    pub fn to_text(&self) -> Result<String, PrefabError> {
+        let mut serde = StrSerializer::new();
+        serde.push_str("chunk: ");
+        serde.insert(&self.chunk).map_err(AssetError::Parse)?;
+        serde.push_str("\nautomita: ");
+        serde.insert(&self.automita).map_err(AssetError::Parse)?;
+        serde.push('\n');
+        Ok(serde.finalize())
+    }
+
+    /// Every cell that isn't `Void`, turned `turns` quarter turns about y then moved to `origin`
+    pub fn cells(&self, origin: IVec3, turns: u8) -> Vec<(IVec3, CellData)> {
+        let blocks = Chunk::<BlockType>::decompress(&self.chunk);
+        let automita = Chunk::<CellData>::decompress(&self.automita);
+        let mut out = Vec::new();
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let block = blocks.get_cell(x, y, z);
+                    if block == BlockType::Void {
+                        continue;
+                    }
+                    let cell = CellData::at_k(block, automita.get_cell(x, y, z).tempreture);
+                    out.push((origin + rotate(IVec3::new(x, y, z), turns), cell));
+                }
+            }
+        }
+        out
+    }
+
+    /// Queue every cell as an edit so it lands between ticks and gets journaled
+    pub fn stamp(&self, origin: IVec3, turns: u8, edits: &mut PendingEdits) {
+        edits.new_action();
+        for (position, cell) in self.cells(origin, turns) {
+            edits.push_at_k(position, cell.block, cell.tempreture);
+        }
+    }
 }
 
-async fn from_bytes(data: Vec<u8>) -> Result<ChunkPrefab, &'static str> {
-    todo!()
+fn rotate(local: IVec3, turns: u8) -> IVec3 {
+    let mut out = local;
+    for _ in 0..turns % 4 {
+        out = IVec3::new(CHUNK_SIZE - 1 - out.z, out.y, out.x);
+    }
+    out
+}
+
+fn from_str(data: String) -> Result<ChunkPrefab, PrefabError> {
+    let mut chunk = None;
+    let mut automita = None;
+    read_fields(data, |field, serde| {
+        match field {
+            "chunk" => chunk = Some(serde.extract()?),
+            "automita" => automita = Some(serde.extract()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    })?;
+    ChunkPrefab::new(
+        chunk.ok_or(AssetError::MissingField("chunk"))?,
+        // most prefabs just want room tempreture
+        automita.unwrap_or(CompressedChunkData::Solid(CellData::default())),
+    )
+}
+
+fn from_bytes(data: &[u8]) -> Result<ChunkPrefab, PrefabError> {
+    let (version, body) =
+        split_version(&data[ChunkPrefab::MAGIC.len()..]).map_err(AssetError::Parse)?;
+    let body = PREFAB_MIGRATIONS
+        .upgrade(version, body)
+        .map_err(AssetError::Parse)?;
+    let mut serde = BinDeSerializer::new(&body);
+    let blocks = palette_blocks(&serde.extract::<Palette>().map_err(AssetError::Parse)?)
+        .map_err(AssetError::Parse)?;
+    let chunk = serde
+        .extract::<CompressedChunkData<SavedCell>>()
+        .map_err(AssetError::Parse)?;
+    let automita = serde
+        .extract::<CompressedChunkData<SavedCell>>()
+        .map_err(AssetError::Parse)?;
+    let chunk = map_compressed(&chunk, |i, cell| {
+        cell.load_block(&blocks)
+            .map_err(|e| MeltdownError::from(e).in_cell(Chunk::<BlockType>::position(i)))
+    })
+    .map_err(|e| AssetError::Parse(e.into()))?;
+    let automita = map_compressed(&automita, |i, cell| {
+        cell.load(&blocks)
+            .map_err(|e| MeltdownError::from(e).in_cell(Chunk::<BlockType>::position(i)))
+    })
+    .map_err(|e| AssetError::Parse(e.into()))?;
+    ChunkPrefab::new(chunk, automita)
+}
+
+/// v1 adds the block palette and writes cells as `SavedCell`s,
+/// v0 used this build's block ids and the 5 byte cell without charge or flux
+fn prefab_v0_to_v1(body: &[u8]) -> bevy::ecs::error::Result<Vec<u8>> {
+    let mut old = BinDeSerializer::new(body);
+    let chunk = old.extract::<CompressedChunkData<BlockType>>()?;
+    let automita = old.extract::<CompressedChunkData<CellData>>()?;
+    let mut new = BinSerializer::new();
+    new.insert(&block_palette())?;
+    new.insert(&map_compressed(&chunk, |_, block| {
+        Ok(SavedCell::of_block(block))
+    })?)?;
+    new.insert(&map_compressed(&automita, |_, cell| {
+        Ok(SavedCell::new(cell))
+    })?)?;
+    Ok(new.finalize())
 }
 
-// impl chunk_serde::Serialize for ChunkPrefab {
-//     fn insert(&self, vec: &mut BinSerializer) -> Result<usize> {
-//         vec.extend_from_slice(b"Phoxb");
-//         let mut used = self.chunk.insert(vec);
-//         used += self.automita.insert(vec);
-//         Ok(used + 5)
-//     }
+/// Prefabs waiting to finish loading before they get stamped
+#[derive(Resource, Default)]
+pub struct PendingStamps(Vec<(Handle<ChunkPrefab>, IVec3, u8)>);
 
-//     fn extract(slice: &[u8]) -> Result<(Self, usize)> {}
-// }
+impl PendingStamps {
+    pub fn push(&mut self, prefab: Handle<ChunkPrefab>, origin: IVec3, turns: u8) {
+        self.0.push((prefab, origin, turns));
+    }
+}
+
+fn stamp_prefabs(
+    mut stamps: ResMut<PendingStamps>,
+    prefabs: Res<Assets<ChunkPrefab>>,
+    asset_server: Res<AssetServer>,
+    mut edits: ResMut<PendingEdits>,
+) {
+    stamps.0.retain(|(handle, origin, turns)| {
+        if let Some(prefab) = prefabs.get(handle) {
+            prefab.stamp(*origin, *turns, &mut edits);
+            return false;
+        }
+        if asset_server.load_state(handle).is_failed() {
+            error!("Failed to load prefab {:?}", handle.path());
+            return false;
+        }
+        true
+    });
+}
+
+#[test]
+fn prefab_round_trips() {
+    use crate::voxels::cellular_automata::FixedNum;
+    let mut blocks = Chunk::solid(BlockType::Void);
+    blocks.set_cell(0, 0, 0, BlockType::Copper);
+    blocks.set_cell(1, 0, 0, BlockType::Uranium);
+    let prefab = ChunkPrefab::new(
+        blocks.compress(),
+        CompressedChunkData::Solid(CellData::at_k(BlockType::Air, FixedNum::lit("400"))),
+    )
+    .unwrap();
+
+    let text = from_str(prefab.to_text().unwrap()).unwrap();
+    let bytes = from_bytes(&prefab.to_bytes().unwrap()).unwrap();
+    assert!(text.chunk == prefab.chunk && text.automita == prefab.automita);
+    assert!(bytes.chunk == prefab.chunk);
+    // tempreture is worked out again from energy so only check what's kept
+    let (CompressedChunkData::Solid(saved), CompressedChunkData::Solid(loaded)) =
+        (&prefab.automita, &bytes.automita)
+    else {
+        panic!("automita should stay solid");
+    };
+    assert_eq!(SavedCell::new(*saved), SavedCell::new(*loaded));
+    assert!((loaded.tempreture - FixedNum::lit("400")).abs() < FixedNum::ONE);
+
+    let cells = prefab.cells(IVec3::new(20, 0, 0), 1);
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[0].0, IVec3::new(20 + CHUNK_SIZE - 1, 0, 0));
+    assert_eq!(cells[1].0, IVec3::new(20 + CHUNK_SIZE - 1, 0, 1));
+    assert_eq!(cells[0].1.tempreture, FixedNum::lit("400"));
+
+    let copper =
+        from_str(include_str!("../../../assets/prefabs/copper_tests.phoxel").to_string()).unwrap();
+    assert_eq!(copper.cells(IVec3::ZERO, 0).len(), 104);
+}
@@ -0,0 +1,519 @@
+use bevy::ecs::error::Result;
+use chunk_serde::{
+    BinDeSerializer, BinError, BinSerializer, CompressedChunkData, Migration, Migrations, Palette,
+    checksum,
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    MeltdownError,
+    voxels::{
+        CHUNK_VOL,
+        block::BlockType,
+        cellular_automata::{ATM_1, CellData, CellFlags, Cells, FixedNum},
+        voxel_chunk::{ChunkId, chunk::Chunk},
+    },
+};
+
+/// upgrades for `PhoxW` world saves
+pub const WORLD_MIGRATIONS: Migrations =
+    Migrations::new(&[world_v0_to_v1 as Migration, world_v1_to_v2 as Migration]);
+
+/// upgrades for `PhoxC` chunk saves
+pub const CHUNK_MIGRATIONS: Migrations =
+    Migrations::new(&[chunk_v0_to_v1 as Migration, chunk_v1_to_v2 as Migration]);
+
+/// Comes straight after the magic in a versioned save,
+/// older saves have a tick or a compression tag here and neither can start with it
+const VERSIONED: u8 = 0xFF;
+
+/// block ids as they were before saves had a palette
+const LEGACY_BLOCKS: [&str; 10] = [
+    "Air", "Copper", "Iron", "Steel", "Uranium", "Water", "Thorium", "Wax", "Rubber", "Void",
+];
+
+pub fn insert_version(serde: &mut BinSerializer, migrations: &Migrations) -> Result<()> {
+    serde.push(VERSIONED);
+    serde.insert(&migrations.current())?;
+    Ok(())
+}
+
+/// Split what comes after the magic into the version and the body
+pub fn split_version(data: &[u8]) -> Result<(u16, &[u8])> {
+    match data.first() {
+        Some(&VERSIONED) => {
+            let (version, used) = <u16 as chunk_serde::Serialize>::extract(&data[1..])?;
+            Ok((version, &data[1 + used..]))
+        }
+        Some(_) => Ok((0, data)),
+        None => Err(BinError::EOF.into()),
+    }
+}
+
+/// Add a checksum of everything written so far to the end
+pub fn append_checksum(serde: &mut BinSerializer) -> Result<()> {
+    let sum = checksum(serde.as_ref());
+    serde.insert(&sum)?;
+    Ok(())
+}
+
+/// Split the checksum off the end of a body, and whether it matched
+pub fn split_checksum(body: &[u8]) -> Result<(&[u8], bool)> {
+    let Some(at) = body.len().checked_sub(4) else {
+        return Err(BinError::EOF.into());
+    };
+    let (body, sum) = body.split_at(at);
+    let (sum, _) = <u32 as chunk_serde::Serialize>::extract(sum)?;
+    Ok((body, sum == checksum(body)))
+}
+
+/// Write a chunk with its length in front and a checksum behind
+/// so a damaged one can be skipped over
+pub fn insert_chunk(
+    serde: &mut BinSerializer,
+    id: &ChunkId,
+    cells: &CompressedChunkData<SavedCell>,
+) -> Result<()> {
+    let mut chunk = BinSerializer::new();
+    chunk.insert(id)?;
+    chunk.insert(cells)?;
+    let bytes = chunk.finalize();
+    serde.insert(&(bytes.len() as u32))?;
+    serde.push_slice(&bytes);
+    serde.insert(&checksum(&bytes))?;
+    Ok(())
+}
+
+/// Read a chunk written by `insert_chunk`
+/// the outer error means the framing is broken and nothing after it can be found,
+/// the inner one that only this chunk is damaged
+pub fn take_chunk(
+    serde: &mut BinDeSerializer,
+) -> Result<Result<(ChunkId, CompressedChunkData<SavedCell>), MeltdownError>> {
+    let len = serde.extract::<u32>()?;
+    let bytes = serde.take(len as usize)?;
+    let sum = serde.extract::<u32>()?;
+    if sum != checksum(bytes) {
+        return Ok(Err(BinError::ChecksumMismatch.into()));
+    }
+    let mut chunk = BinDeSerializer::new(bytes);
+    let id = match chunk.extract::<ChunkId>() {
+        Ok(id) => id,
+        Err(e) => return Ok(Err(e.into())),
+    };
+    Ok(chunk
+        .extract::<CompressedChunkData<SavedCell>>()
+        .and_then(|cells| check_cell_count(&cells).map(|_| cells))
+        .map(|cells| (id, cells))
+        .map_err(|e| MeltdownError::from(e).in_chunk(id)))
+}
+
+/// A chunk that expands to the wrong number of cells would panic when decompressed
+pub fn check_cell_count<T>(cells: &CompressedChunkData<T>) -> Result<()> {
+    match cells.cell_count() {
+        None => Ok(()),
+        Some(CHUNK_VOL) => Ok(()),
+        Some(count) => Err(BinError::CellCount(count, CHUNK_VOL).into()),
+    }
+}
+
+/// What happened while loading a world, a damaged save still loads everything it can
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub loaded: usize,
+    /// chunks that failed there checksum or didn't make sense, by place in the save
+    pub corrupted: Vec<(u64, MeltdownError)>,
+    /// chunks that couldn't be found because the data before them was broken
+    pub skipped: u64,
+    /// the checksum over the whole file matched
+    pub intact: bool,
+}
+
+impl LoadReport {
+    pub fn is_clean(&self) -> bool {
+        self.intact && self.corrupted.is_empty() && self.skipped == 0
+    }
+}
+
+impl std::fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "loaded {} chunks", self.loaded)?;
+        if !self.intact {
+            write!(f, "; file checksum does not match")?;
+        }
+        for (index, error) in &self.corrupted {
+            write!(f, "; chunk #{index} corrupted: {error}")?;
+        }
+        if self.skipped > 0 {
+            write!(f, "; {} chunks skipped", self.skipped)?;
+        }
+        Ok(())
+    }
+}
+
+/// every block this build knows about, by name
+pub fn block_palette() -> Palette {
+    Palette::new(BlockType::iter().map(|b| b.to_string()))
+}
+
+/// What each id in a save's palette is in this build
+pub fn palette_blocks(palette: &Palette) -> Result<Vec<BlockType>> {
+    palette.remap(|name| BlockType::iter().find(|b| b.to_string() == name))
+}
+
+/// A cell as it's written to a save, `block` is an index into the save's palette
+/// the rest of the cell is worked out from energy when it's loaded
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SavedCell {
+    block: u8,
+    energy: FixedNum,
+    charge: FixedNum,
+    flux: FixedNum,
+}
+
+impl SavedCell {
+    pub fn new(cell: CellData) -> SavedCell {
+        SavedCell {
+            block: cell.block as u8,
+            energy: cell.energy,
+            charge: cell.charge,
+            flux: cell.flux,
+        }
+    }
+
+    /// just the block, for a prefab's shape
+    pub fn of_block(block: BlockType) -> SavedCell {
+        SavedCell {
+            block: block as u8,
+            ..Default::default()
+        }
+    }
+
+    /// fails if the block id isn't in the palette
+    pub fn load_block(self, blocks: &[BlockType]) -> Result<BlockType, BinError> {
+        blocks
+            .get(self.block as usize)
+            .copied()
+            .ok_or(BinError::InvalidId(self.block))
+    }
+
+    /// fails if the block id isn't in the palette
+    pub fn load(self, blocks: &[BlockType]) -> Result<CellData, BinError> {
+        let block = self.load_block(blocks)?;
+        let mut cell = CellData {
+            block,
+            energy: self.energy,
+            tempreture: FixedNum::ONE, // Will be set later
+            density: FixedNum::ONE,    // Will be set later
+            presure: ATM_1,            // Will be set later
+            charge: self.charge,
+            flux: self.flux,
+            flags: CellFlags::empty(),
+        };
+        cell.set_tempreture();
+        cell.set_phase();
+        cell.set_density();
+        cell.set_presure(ATM_1);
+        Ok(cell)
+    }
+}
+
+impl chunk_serde::Serialize for SavedCell {
+    fn insert(&self, vec: &mut BinSerializer) -> Result<usize> {
+        vec.push(self.block);
+        for value in [self.energy, self.charge, self.flux] {
+            for byte in value.to_be_bytes() {
+                vec.push(byte);
+            }
+        }
+        Ok(13)
+    }
+
+    fn extract(slice: &[u8]) -> Result<(Self, usize)> {
+        if slice.len() < 13 {
+            return Err(BinError::EOF.into());
+        }
+        let fixed = |at: usize| FixedNum::from_be_bytes(slice[at..at + 4].try_into().unwrap());
+        Ok((
+            SavedCell {
+                block: slice[0],
+                energy: fixed(1),
+                charge: fixed(5),
+                flux: fixed(9),
+            },
+            13,
+        ))
+    }
+}
+
+/// Cells from before saves had a version, only block and energy were kept
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct LegacyCell {
+    block: u8,
+    energy: FixedNum,
+}
+
+impl LegacyCell {
+    fn upgrade(self) -> SavedCell {
+        SavedCell {
+            block: self.block,
+            energy: self.energy,
+            charge: FixedNum::ZERO,
+            flux: FixedNum::ZERO,
+        }
+    }
+}
+
+impl chunk_serde::Serialize for LegacyCell {
+    fn insert(&self, vec: &mut BinSerializer) -> Result<usize> {
+        vec.push(self.block);
+        for byte in self.energy.to_be_bytes() {
+            vec.push(byte);
+        }
+        Ok(5)
+    }
+
+    fn extract(slice: &[u8]) -> Result<(Self, usize)> {
+        if slice.len() < 5 {
+            return Err(BinError::EOF.into());
+        }
+        Ok((
+            LegacyCell {
+                block: slice[0],
+                energy: FixedNum::from_be_bytes(slice[1..5].try_into().unwrap()),
+            },
+            5,
+        ))
+    }
+}
+
+pub fn save_cells(cells: &Cells) -> CompressedChunkData<SavedCell> {
+    if cells.is_solid() {
+        return CompressedChunkData::Solid(SavedCell::new(cells.get_by_index(0)));
+    }
+    let mut saved = Chunk::<SavedCell>::empty();
+    for i in 0..CHUNK_VOL {
+        saved.set_by_index(i, SavedCell::new(cells.get_by_index(i)));
+    }
+    saved.compress()
+}
+
+/// Errors say which cell had a block that isn't in `blocks`
+pub fn load_cells(
+    saved: &CompressedChunkData<SavedCell>,
+    blocks: &[BlockType],
+) -> Result<Cells, MeltdownError> {
+    let load = |i: usize, cell: &SavedCell| {
+        cell.load(blocks)
+            .map_err(|e| MeltdownError::from(e).in_cell(Cells::position(i)))
+    };
+    if let CompressedChunkData::Solid(cell) = saved {
+        return load(0, cell).map(Cells::solid);
+    }
+    let saved = Chunk::decompress(saved);
+    let mut cells = Cells::empty();
+    cells.set_not_solid();
+    for i in 0..CHUNK_VOL {
+        cells.set_by_index(i, load(i, &saved.get_by_index(i))?);
+    }
+    Ok(cells)
+}
+
+/// Run `f` over every cell of something that isn't a `Cells`, like a prefab.
+/// a solid chunk stays solid
+pub fn map_compressed<A, B>(
+    data: &CompressedChunkData<A>,
+    f: impl Fn(usize, A) -> Result<B, MeltdownError>,
+) -> Result<CompressedChunkData<B>, MeltdownError>
+where
+    A: Copy + Default + PartialEq,
+    B: Copy + Default + PartialEq,
+{
+    if let CompressedChunkData::Solid(cell) = data {
+        return f(0, *cell).map(CompressedChunkData::Solid);
+    }
+    let from = Chunk::decompress(data);
+    let mut to = Chunk::<B>::empty();
+    to.set_not_solid();
+    for i in 0..CHUNK_VOL {
+        to.set_by_index(i, f(i, from.get_by_index(i))?);
+    }
+    Ok(to.compress())
+}
+
+/// v1 adds the block palette, moves the seed up front and keeps charge and flux
+fn world_v0_to_v1(body: &[u8]) -> Result<Vec<u8>> {
+    let mut old = BinDeSerializer::new(body);
+    let tick = old.extract::<u64>()?;
+    let len = old.extract::<u64>()?;
+    let mut chunks = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        let id = old.extract::<ChunkId>()?;
+        let cells = old.extract::<CompressedChunkData<LegacyCell>>()?;
+        chunks.push((id, cells));
+    }
+    // the seed was tacked on the end, and before that not saved at all
+    let seed = if old.remaining() > 0 {
+        old.extract::<u64>()?
+    } else {
+        0
+    };
+
+    let mut new = BinSerializer::new();
+    new.insert(&tick)?;
+    new.insert(&seed)?;
+    new.insert(&Palette::new(LEGACY_BLOCKS))?;
+    new.insert(&len)?;
+    for (id, cells) in chunks {
+        new.insert(&id)?;
+        new.insert(&cells.map(LegacyCell::upgrade))?;
+    }
+    Ok(new.finalize())
+}
+
+/// v2 frames each chunk with a length and checksum, and checksums the whole body
+fn world_v1_to_v2(body: &[u8]) -> Result<Vec<u8>> {
+    let mut old = BinDeSerializer::new(body);
+    let tick = old.extract::<u64>()?;
+    let seed = old.extract::<u64>()?;
+    let palette = old.extract::<Palette>()?;
+    let len = old.extract::<u64>()?;
+
+    let mut new = BinSerializer::new();
+    new.insert(&tick)?;
+    new.insert(&seed)?;
+    new.insert(&palette)?;
+    new.insert(&len)?;
+    for _ in 0..len {
+        let id = old.extract::<ChunkId>()?;
+        let cells = old.extract::<CompressedChunkData<SavedCell>>()?;
+        insert_chunk(&mut new, &id, &cells)?;
+    }
+    append_checksum(&mut new)?;
+    Ok(new.finalize())
+}
+
+/// v2 adds a checksum to the end
+fn chunk_v1_to_v2(body: &[u8]) -> Result<Vec<u8>> {
+    let mut new = BinSerializer::new();
+    new.push_slice(body);
+    append_checksum(&mut new)?;
+    Ok(new.finalize())
+}
+
+/// v1 adds the block palette and keeps charge and flux
+fn chunk_v0_to_v1(body: &[u8]) -> Result<Vec<u8>> {
+    let mut old = BinDeSerializer::new(body);
+    let cells = old.extract::<CompressedChunkData<LegacyCell>>()?;
+    let mut new = BinSerializer::new();
+    new.insert(&Palette::new(LEGACY_BLOCKS))?;
+    new.insert(&cells.map(LegacyCell::upgrade))?;
+    Ok(new.finalize())
+}
+
+#[test]
+fn legacy_world_loads() {
+    // tick 5, one chunk of solid water, no seed
+    let mut old = BinSerializer::new();
+    old.insert(&5u64).unwrap();
+    old.insert(&1u64).unwrap();
+    old.insert(&ChunkId::new(0, -1, 0)).unwrap();
+    old.insert(&CompressedChunkData::Solid(LegacyCell {
+        block: BlockType::Water as u8,
+        energy: FixedNum::lit("100"),
+    }))
+    .unwrap();
+    let old = old.finalize();
+
+    let (version, body) = split_version(&old).unwrap();
+    assert_eq!(version, 0);
+    let body = WORLD_MIGRATIONS.upgrade(version, body).unwrap();
+    let (body, intact) = split_checksum(&body).unwrap();
+    assert!(intact);
+    let mut new = BinDeSerializer::new(body);
+    assert_eq!(new.extract::<u64>().unwrap(), 5);
+    assert_eq!(new.extract::<u64>().unwrap(), 0);
+    let blocks = palette_blocks(&new.extract::<Palette>().unwrap()).unwrap();
+    assert_eq!(new.extract::<u64>().unwrap(), 1);
+    let (id, cells) = take_chunk(&mut new).unwrap().unwrap();
+    assert_eq!(id, ChunkId::new(0, -1, 0));
+    let cells = load_cells(&cells, &blocks).unwrap();
+    assert_eq!(cells.get_cell(3, 3, 3).get_block_type(), BlockType::Water);
+    assert_eq!(cells.get_cell(3, 3, 3).energy, FixedNum::lit("100"));
+}
+
+#[test]
+fn legacy_void_stays_void() {
+    // id 9 was Void before ControlRod and SpentFuel were added in front of it
+    let mut old = BinSerializer::new();
+    old.insert(&CompressedChunkData::Solid(LegacyCell {
+        block: 9,
+        energy: FixedNum::ZERO,
+    }))
+    .unwrap();
+    let old = old.finalize();
+
+    let body = CHUNK_MIGRATIONS.upgrade(0, &old).unwrap();
+    let (body, intact) = split_checksum(&body).unwrap();
+    assert!(intact);
+    let mut new = BinDeSerializer::new(body);
+    let blocks = palette_blocks(&new.extract::<Palette>().unwrap()).unwrap();
+    let cells = load_cells(&new.extract().unwrap(), &blocks).unwrap();
+    assert_eq!(cells.get_cell(0, 0, 0).get_block_type(), BlockType::Void);
+}
+
+#[test]
+fn block_outside_the_palette_names_the_cell() {
+    let mut chunk = Chunk::<SavedCell>::empty();
+    chunk.set_by_index(
+        Cells::index(1, 2, 3),
+        SavedCell {
+            block: 7,
+            ..Default::default()
+        },
+    );
+    let error = load_cells(&chunk.compress(), &[BlockType::Air]).unwrap_err();
+    assert_eq!(error.cell(), Some(bevy::math::IVec3::new(1, 2, 3)));
+    assert!(matches!(
+        error.root(),
+        MeltdownError::Bin(BinError::InvalidId(7))
+    ));
+}
+
+#[test]
+fn palette_follows_names() {
+    // a save where the ids are in a different order to now
+    let palette = Palette::new(["Water", "Air", "Copper"]);
+    let blocks = palette_blocks(&palette).unwrap();
+    assert_eq!(
+        blocks,
+        vec![BlockType::Water, BlockType::Air, BlockType::Copper]
+    );
+    assert!(palette_blocks(&Palette::new(["Unobtainium"])).is_err());
+}
+
+#[test]
+fn damaged_chunk_is_skipped() {
+    let cells = CompressedChunkData::Solid(SavedCell::default());
+    let mut serde = BinSerializer::new();
+    insert_chunk(&mut serde, &ChunkId::new(1, 2, 3), &cells).unwrap();
+    insert_chunk(&mut serde, &ChunkId::new(4, 5, 6), &cells).unwrap();
+    let mut data = serde.finalize();
+    // flip a bit in the first chunk's id
+    data[6] ^= 1;
+
+    let mut serde = BinDeSerializer::new(&data);
+    assert!(take_chunk(&mut serde).unwrap().is_err());
+    let (id, _) = take_chunk(&mut serde).unwrap().unwrap();
+    assert_eq!(id, ChunkId::new(4, 5, 6));
+    // the framing of a third chunk isn't there at all
+    assert!(take_chunk(&mut serde).is_err());
+}
+
+#[test]
+fn wrong_cell_count_is_caught() {
+    let short = CompressedChunkData::RunLen(vec![(SavedCell::default(), 10)]);
+    assert!(check_cell_count(&short).is_err());
+    let full = CompressedChunkData::RunLen(vec![(SavedCell::default(), CHUNK_VOL as u16)]);
+    assert!(check_cell_count(&full).is_ok());
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use phoxels::prelude::PhoxelGenerator;
+
+use crate::{
+    GameState,
+    menu::MapSize,
+    player::Player,
+    voxels::{
+        BlockType, ChunkId, ChunkManager, VoxleMaterialHandle,
+        cellular_automata::{ApplyStep, Cells, can_modify_world},
+    },
+};
+
+/// Most chunks brought back in one frame, so walking fast doesn't stall a frame
+const MAX_LOADS: usize = 64;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<StreamDistance>();
+    // chunks can only come and go between ticks, same as any other edit
+    app.add_systems(
+        Update,
+        stream_chunks
+            .after(ApplyStep::Edit)
+            .run_if(can_modify_world)
+            .run_if(in_state(GameState::Game)),
+    );
+}
+
+/// How far from the player chunks stay loaded, counted in chunks along x and z.
+/// Every chunk in a column is loaded so the whole height of the map keeps ticking.
+/// Chunks past `unload` are packed into the `ChunkManager` and despawned,
+/// they come back once the player is within `load` of them again
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct StreamDistance {
+    pub load: u32,
+    /// kept bigger than `load` so chunks on the edge don't flicker in and out
+    pub unload: u32,
+}
+
+impl Default for StreamDistance {
+    fn default() -> Self {
+        StreamDistance {
+            load: 8,
+            unload: 10,
+        }
+    }
+}
+
+impl StreamDistance {
+    pub fn in_load(&self, center: ChunkId, id: ChunkId) -> bool {
+        column_distance(center, id) <= self.load
+    }
+
+    pub fn in_unload(&self, center: ChunkId, id: ChunkId) -> bool {
+        column_distance(center, id) <= self.unload
+    }
+}
+
+fn column_distance(a: ChunkId, b: ChunkId) -> u32 {
+    a.x.abs_diff(b.x).max(a.z.abs_diff(b.z))
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    player: Single<&Transform, With<Player>>,
+    distance: Res<StreamDistance>,
+    map_size: Res<MapSize>,
+    mut manager: ResMut<ChunkManager>,
+    chunks: Query<(Entity, &ChunkId, &Cells)>,
+    generator: Res<PhoxelGenerator<BlockType, ChunkId>>,
+    material: Res<VoxleMaterialHandle>,
+) {
+    let center = ChunkId::from_translation(player.translation);
+
+    let mut unloaded = 0;
+    for (entity, id, cells) in &chunks {
+        if distance.in_unload(center, *id) {
+            continue;
+        }
+        manager.unload(*id, cells);
+        commands.entity(entity).despawn();
+        unloaded += 1;
+    }
+
+    let load = distance.load as i32;
+    let size = map_size.0.as_ivec3();
+    let mut loaded = 0;
+    'columns: for x in (center.x - load).max(0)..=(center.x + load).min(size.x - 1) {
+        for z in (center.z - load).max(0)..=(center.z + load).min(size.z - 1) {
+            for y in -size.y..1 {
+                let id = ChunkId::new(x, y, z);
+                if manager.get_chunk(&id).is_some() {
+                    continue;
+                }
+                if loaded >= MAX_LOADS {
+                    break 'columns;
+                }
+                loaded += 1;
+                // anything that was never loaded gets generated like the rest of the map
+                match manager.get_unloaded(&id) {
+                    Some(cells) => commands.spawn((cells, id)),
+                    None => commands.spawn((id, generator.clone(), MeshMaterial3d(material.get()))),
+                };
+            }
+        }
+    }
+
+    if unloaded > 0 || loaded > 0 {
+        debug!(
+            "streamed in {} chunks and out {} around {}",
+            loaded, unloaded, center
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use bevy::ecs::error::{BevyError, Result};
+use chunk_serde::{CompressedChunkData, Serialize, StrError, StrSerializer};
+
+use crate::voxels::{
+    CHUNK_VOL,
+    block::BlockType,
+    cellular_automata::{CellData, CellFlags, Cells, FixedNum},
+    voxel_chunk::{ChunkId, chunk::Chunk, save::check_cell_count},
+};
+
+/// What can go wrong reading a `field: value` asset, shared by prefabs and blueprints
+#[derive(Debug, thiserror::Error)]
+pub enum AssetError {
+    #[error("Failed to read: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not valid text: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Failed to parse: {0}")]
+    Parse(BevyError),
+    #[error("Unknown field {0}")]
+    UnknownField(String),
+    #[error("Missing {0}")]
+    MissingField(&'static str),
+}
+
+/// Read `field: value` pairs to the end of `data`,
+/// `read` takes the value of a field it knows and returns false for one it doesn't
+pub fn read_fields(
+    data: String,
+    mut read: impl FnMut(&str, &mut StrSerializer) -> Result<bool>,
+) -> Result<(), AssetError> {
+    let mut serde = StrSerializer::from(data);
+    while !serde.is_finished() {
+        let field = serde.word().map_err(AssetError::Parse)?;
+        serde.expect(':').map_err(AssetError::Parse)?;
+        if !read(&field, &mut serde).map_err(AssetError::Parse)? {
+            return Err(AssetError::UnknownField(field));
+        }
+    }
+    Ok(())
+}
+
+/// Starts every text export, followed by the version
+const MAGIC: &str = "PhoxT";
+const VERSION: u16 = 1;
+const PHASES: &[&str] = &["solid", "liquid", "gas"];
+
+/// A cell as it's written to a text export, `(Copper, 293.15, solid)`
+/// phase follows from the tempreture so it's only there for people reading the file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TextCell {
+    block: BlockType,
+    tempreture: FixedNum,
+}
+
+impl TextCell {
+    fn new(cell: CellData) -> TextCell {
+        TextCell {
+            block: cell.block,
+            tempreture: cell.tempreture,
+        }
+    }
+
+    fn load(self) -> CellData {
+        CellData::at_k(self.block, self.tempreture)
+    }
+
+    fn phase(&self) -> &'static str {
+        let flags = self.load().flags;
+        if flags.contains(CellFlags::IS_GAS) {
+            "gas"
+        } else if flags.contains(CellFlags::IS_LIQUID) {
+            "liquid"
+        } else {
+            "solid"
+        }
+    }
+}
+
+impl Serialize for TextCell {
+    fn insert(&self, vec: &mut chunk_serde::BinSerializer) -> Result<usize> {
+        vec.push(self.block as u8);
+        vec.push_slice(&self.tempreture.to_be_bytes());
+        Ok(5)
+    }
+
+    fn extract(slice: &[u8]) -> Result<(Self, usize)> {
+        let (block, _) = BlockType::extract(slice)?;
+        let Some(k) = slice.get(1..5) else {
+            return Err(chunk_serde::BinError::EOF.into());
+        };
+        let tempreture = FixedNum::from_be_bytes(k.try_into()?);
+        Ok((TextCell { block, tempreture }, 5))
+    }
+
+    fn insert_str(&self, serializer: &mut StrSerializer) -> Result<usize> {
+        let start = serializer.len();
+        serializer.write(format_args!(
+            "({}, {}, {})",
+            self.block,
+            self.tempreture,
+            self.phase()
+        ))?;
+        Ok(serializer.len() - start)
+    }
+
+    fn extract_str(str: &str) -> Result<(Self, usize)> {
+        let mut used = chunk_serde::expect_char(str, '(')?;
+        let (block, len) = BlockType::extract_str(&str[used..])?;
+        used += len;
+        used += chunk_serde::expect_char(&str[used..], ',')?;
+        let (k, len) = chunk_serde::take_word(&str[used..])?;
+        let tempreture = k
+            .parse::<FixedNum>()
+            .map_err(|e| BevyError::from(format!("Invalid tempreture {k}: {e}")))?;
+        used += len;
+        used += chunk_serde::expect_char(&str[used..], ',')?;
+        let (phase, len) = chunk_serde::take_word(&str[used..])?;
+        if !PHASES.contains(&phase) {
+            return Err(StrError::InValidName(phase.to_string(), PHASES).into());
+        }
+        used += len;
+        used += chunk_serde::expect_char(&str[used..], ')')?;
+        Ok((TextCell { block, tempreture }, used))
+    }
+}
+
+/// A world or some of one read back from text
+/// `tick` and `seed` are only there if it was a whole world
+#[derive(Default)]
+pub struct TextWorld {
+    pub tick: Option<u64>,
+    pub seed: Option<u64>,
+    pub chunks: Vec<(ChunkId, Cells)>,
+}
+
+pub fn insert_header(serde: &mut StrSerializer) -> Result<()> {
+    serde.write(format_args!("{MAGIC} {VERSION}\n"))?;
+    Ok(())
+}
+
+pub fn insert_world(serde: &mut StrSerializer, tick: u64, seed: u64) -> Result<()> {
+    serde.write(format_args!("tick: {tick}\nseed: {seed}\n"))?;
+    Ok(())
+}
+
+/// `chunk: (x, y, z) RunLen([...])` with every run on its own line so it diffs nicely
+pub fn insert_chunk(serde: &mut StrSerializer, id: &ChunkId, cells: &Cells) -> Result<()> {
+    serde.push_str("chunk: ");
+    serde.insert(id)?;
+    serde.push(' ');
+    serde.insert(&text_cells(cells))?;
+    serde.push('\n');
+    Ok(())
+}
+
+fn text_cells(cells: &Cells) -> CompressedChunkData<TextCell> {
+    if cells.is_solid() {
+        return CompressedChunkData::Solid(TextCell::new(cells.get_by_index(0)));
+    }
+    let mut text = Chunk::<TextCell>::empty();
+    for i in 0..CHUNK_VOL {
+        text.set_by_index(i, TextCell::new(cells.get_by_index(i)));
+    }
+    text.compress()
+}
+
+fn load_text_cells(text: &CompressedChunkData<TextCell>) -> Cells {
+    if let CompressedChunkData::Solid(cell) = text {
+        return Cells::solid(cell.load());
+    }
+    let text = Chunk::decompress(text);
+    let mut cells = Cells::empty();
+    cells.set_not_solid();
+    for i in 0..CHUNK_VOL {
+        cells.set_by_index(i, text.get_by_index(i).load());
+    }
+    cells
+}
+
+pub fn parse(data: String) -> Result<TextWorld> {
+    let mut serde = StrSerializer::from(data);
+    if serde.word()? != MAGIC {
+        return Err(BevyError::from("Not a text export"));
+    }
+    let version = serde.extract::<u16>()?;
+    if version > VERSION {
+        return Err(chunk_serde::BinError::FutureVersion(version, VERSION).into());
+    }
+    let mut world = TextWorld::default();
+    while !serde.is_finished() {
+        let field = serde.word()?;
+        serde.expect(':')?;
+        match field.as_str() {
+            "tick" => world.tick = Some(serde.extract()?),
+            "seed" => world.seed = Some(serde.extract()?),
+            "chunk" => {
+                let id = serde.extract::<ChunkId>()?;
+                let cells = serde.extract::<CompressedChunkData<TextCell>>()?;
+                check_cell_count(&cells)?;
+                world.chunks.push((id, load_text_cells(&cells)));
+            }
+            _ => {
+                return Err(StrError::InValidName(field, &["tick", "seed", "chunk"]).into());
+            }
+        }
+    }
+    Ok(world)
+}
+
+#[test]
+fn text_round_trips() {
+    let mut cells = Cells::solid(CellData::at_k(BlockType::Water, FixedNum::lit("300")));
+    cells.set_not_solid();
+    cells.set_by_index(0, CellData::at_k(BlockType::Copper, FixedNum::lit("600")));
+    cells.set_by_index(1, CellData::at_k(BlockType::Water, FixedNum::lit("400")));
+
+    let mut serde = StrSerializer::new();
+    insert_header(&mut serde).unwrap();
+    insert_world(&mut serde, 12, 34).unwrap();
+    insert_chunk(&mut serde, &ChunkId::new(1, -2, 3), &cells).unwrap();
+    let text = serde.finalize();
+    assert!(text.contains("(Copper, 600, solid)"));
+    assert!(text.contains("(Water, 400, gas)"));
+
+    let world = parse(text).unwrap();
+    assert_eq!((world.tick, world.seed), (Some(12), Some(34)));
+    let (id, loaded) = &world.chunks[0];
+    assert_eq!(*id, ChunkId::new(1, -2, 3));
+    assert!(*loaded == cells);
+}